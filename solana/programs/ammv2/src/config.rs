@@ -0,0 +1,95 @@
+//! 전역 풀 팩토리 설정(`AmmConfig`)과 풀 레지스트리.
+//!
+//! `AmmConfig`는 프로그램에 하나 ([b"amm_config"])이며 새 풀의 admin, 기본 수수료, 생성 수수료를
+//! 정한다. 레지스트리 페이지 ([b"pool_registry", page_index(u32 LE)])에는 풀이 생성 순서대로
+//! `POOLS_PER_PAGE`개씩 기록되므로, 클라이언트는 `pool_count`로 페이지 수를 구해 차례로 읽으면 된다.
+
+use crate::{DexError, FEE_TIERS_BPS, PROTOCOL_FEE_DENOMINATOR};
+use anchor_lang::prelude::*;
+
+/// 레지스트리 페이지 하나에 담기는 풀 수
+pub const POOLS_PER_PAGE: u64 = 64;
+
+#[account]
+pub struct AmmConfig {
+    // 새 풀의 admin
+    pub admin: Pubkey,
+    // 풀 생성 수수료(lamports)를 받는 계정
+    pub fee_receiver: Pubkey,
+    // initialize_pool에 fee_bps가 없을 때 쓰는 티어
+    pub default_trade_fee_bps: u64,
+    // 새 풀의 프로토콜 몫 (PROTOCOL_FEE_DENOMINATOR 기준)
+    pub default_protocol_fee_numerator: u64,
+    pub creation_fee: u64,
+    // 등록된 풀 수 — 다음 풀이 들어갈 레지스트리 위치
+    pub pool_count: u64,
+}
+
+impl AmmConfig {
+    // 계산: discriminator(8) + pubkey 2*32 + u64 4*8
+    pub const LEN: usize = 8 + 2 * 32 + 4 * 8; // = 104
+
+    /// 다음 풀이 기록될 레지스트리 페이지
+    pub fn registry_page(&self) -> u32 {
+        (self.pool_count / POOLS_PER_PAGE) as u32
+    }
+
+    /// 다음 레지스트리 위치(`registry`는 `registry_page()` 페이지)에 풀을 기록한다
+    pub fn register(&mut self, registry: &mut PoolRegistry, entry: PoolEntry) -> Result<()> {
+        registry.page_index = self.registry_page();
+        registry.pools.push(entry);
+        self.pool_count = self
+            .pool_count
+            .checked_add(1)
+            .ok_or(DexError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn apply(&mut self, params: &AmmConfigParams) -> Result<()> {
+        require!(
+            FEE_TIERS_BPS.contains(&params.default_trade_fee_bps),
+            DexError::InvalidFeeTier
+        );
+        require!(
+            params.default_protocol_fee_numerator <= PROTOCOL_FEE_DENOMINATOR,
+            DexError::InvalidProtocolFee
+        );
+        self.fee_receiver = params.fee_receiver;
+        self.default_trade_fee_bps = params.default_trade_fee_bps;
+        self.default_protocol_fee_numerator = params.default_protocol_fee_numerator;
+        self.creation_fee = params.creation_fee;
+        Ok(())
+    }
+}
+
+/// `initialize_amm_config` / `update_amm_config`로 설정하는 값
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmmConfigParams {
+    pub fee_receiver: Pubkey,
+    pub default_trade_fee_bps: u64,
+    pub default_protocol_fee_numerator: u64,
+    pub creation_fee: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolEntry {
+    pub amm_info: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub lp_mint: Pubkey,
+}
+
+impl PoolEntry {
+    pub const LEN: usize = 4 * 32;
+}
+
+#[account]
+pub struct PoolRegistry {
+    pub page_index: u32,
+    pub pools: Vec<PoolEntry>,
+}
+
+impl PoolRegistry {
+    // 계산: discriminator(8) + u32 4 + vec 길이(4) + PoolEntry 64*128
+    pub const LEN: usize = 8 + 4 + 4 + POOLS_PER_PAGE as usize * PoolEntry::LEN; // = 8208
+}
@@ -0,0 +1,556 @@
+//! 스왑 곡선: constant product (x*y=k)와 Curve 방식 2-코인 StableSwap.
+//!
+//! StableSwap 불변량 (n = 2, Ann = A * n^n):
+//!   Ann * (x + y) + D = Ann * D + D^3 / (4xy)
+//! D와 y는 Newton 반복으로 구하며, 반올림은 항상 풀에 유리한 쪽으로 한다.
+
+use crate::math::{self, Rounding};
+use crate::{calculate_amount_in, calculate_amount_out, DexError, U256};
+use anchor_lang::prelude::*;
+
+/// StableSwap amplification 계수 범위
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+/// ramp 한 번에 바꿀 수 있는 최대 배수와 최소 기간 (초)
+pub const MAX_AMP_CHANGE: u64 = 10;
+pub const MIN_RAMP_DURATION: i64 = 86_400;
+/// Newton 반복 최대 횟수 — 넘으면 실패
+pub const MAX_ITERATIONS: usize = 255;
+
+const N_COINS: u64 = 2;
+/// `spot_price`가 돌려주는 분자/분모의 최대 비트 수 — 가격 비교 곱이 U256 안에 들도록
+const SPOT_PRICE_BITS: usize = 96;
+
+/// 풀 곡선 종류. `AmmInfo.curve_type`에는 variant 순서대로 u64로 저장된다.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveType {
+    ConstantProduct,
+    StableSwap,
+}
+
+impl CurveType {
+    pub fn from_u64(curve_type: u64) -> Result<Self> {
+        match curve_type {
+            0 => Ok(CurveType::ConstantProduct),
+            1 => Ok(CurveType::StableSwap),
+            _ => err!(DexError::InvalidCurveType),
+        }
+    }
+}
+
+/// `amount_in`을 넣었을 때 받는 양 (수수료는 입력에서 차감)
+pub fn swap_amount_out(
+    curve_type: CurveType,
+    amp: u64,
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<u64> {
+    match curve_type {
+        CurveType::ConstantProduct => calculate_amount_out(
+            amount_in,
+            reserve_in,
+            reserve_out,
+            fee_numerator,
+            fee_denominator,
+        ),
+        CurveType::StableSwap => {
+            require!(amount_in > 0, DexError::InvalidAmount);
+            require!(
+                reserve_in > 0 && reserve_out > 0,
+                DexError::InsufficientLiquidity
+            );
+            require!(fee_numerator < fee_denominator, DexError::InvalidFeeTier);
+
+            let amount_in_after_fee = math::mul_div(
+                amount_in,
+                fee_denominator - fee_numerator,
+                fee_denominator,
+                Rounding::Down,
+            )? as u128;
+            let d = compute_d(amp, reserve_in as u128, reserve_out as u128)?;
+            let new_reserve_out = compute_y(amp, reserve_in as u128 + amount_in_after_fee, d)?;
+            // 반올림 오차 1은 풀 몫
+            let amount_out = (reserve_out as u128)
+                .saturating_sub(new_reserve_out)
+                .saturating_sub(1);
+            u64::try_from(amount_out).map_err(|_| error!(DexError::MathOverflow))
+        }
+    }
+}
+
+/// 정확히 `amount_out`을 받기 위해 필요한 최소 입력량 (`swap_amount_out`의 역함수, 올림)
+pub fn swap_amount_in(
+    curve_type: CurveType,
+    amp: u64,
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<u64> {
+    match curve_type {
+        CurveType::ConstantProduct => calculate_amount_in(
+            amount_out,
+            reserve_in,
+            reserve_out,
+            fee_numerator,
+            fee_denominator,
+        ),
+        CurveType::StableSwap => {
+            require!(amount_out > 0, DexError::InvalidAmount);
+            require!(
+                reserve_in > 0 && reserve_out > 0,
+                DexError::InsufficientLiquidity
+            );
+            require!(amount_out < reserve_out, DexError::InsufficientLiquidity);
+            require!(fee_numerator < fee_denominator, DexError::InvalidFeeTier);
+
+            let d = compute_d(amp, reserve_in as u128, reserve_out as u128)?;
+            let new_reserve_in = compute_y(amp, (reserve_out - amount_out) as u128, d)?;
+            let amount_in_after_fee = new_reserve_in
+                .checked_sub(reserve_in as u128)
+                .ok_or(DexError::MathOverflow)?
+                + 1;
+            let amount_in = (amount_in_after_fee * fee_denominator as u128)
+                .div_ceil((fee_denominator - fee_numerator) as u128);
+            u64::try_from(amount_in).map_err(|_| error!(DexError::InvalidAmount))
+        }
+    }
+}
+
+/// 첫 공급 시 발행할 LP 총량: constant product는 sqrt(xy), StableSwap은 D
+pub fn initial_liquidity(curve_type: CurveType, amp: u64, coin: u64, pc: u64) -> Result<u64> {
+    match curve_type {
+        CurveType::ConstantProduct => Ok(math::sqrt(coin as u128 * pc as u128)),
+        CurveType::StableSwap => u64::try_from(compute_d(amp, coin as u128, pc as u128)?)
+            .map_err(|_| error!(DexError::MathOverflow)),
+    }
+}
+
+/// 한쪽 토큰 `amount_in`만으로 공급할 때 먼저 스왑할 양.
+/// 스왑 후 남은 입력과 받은 출력이 스왑 후 풀 비율과 같아지도록 한다.
+///
+/// constant product는 Uniswap v2 zap의 닫힌 해 (f = 수수료율):
+///   s = (sqrt(r²(2-f)² + 4(1-f)·a·r) - r(2-f)) / (2(1-f))
+/// StableSwap은 닫힌 해가 없어, 교환 비율을 상수로 본 해를 실제 비율로 한 번 보정해 쓴다.
+pub fn zap_swap_amount(
+    curve_type: CurveType,
+    amp: u64,
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<u64> {
+    require!(amount_in > 0, DexError::InvalidAmount);
+    require!(
+        reserve_in > 0 && reserve_out > 0,
+        DexError::InsufficientLiquidity
+    );
+    require!(fee_numerator < fee_denominator, DexError::InvalidFeeTier);
+
+    match curve_type {
+        CurveType::ConstantProduct => {
+            // 분자/분모에 den을 곱해 정수로: (sqrt(b² + 4·g·den·a·r) - b) / 2g, b = r(den + g), g = den - num
+            let denominator = U256::from(fee_denominator);
+            let g = U256::from(fee_denominator - fee_numerator);
+            let reserve_in = U256::from(reserve_in);
+            let b = reserve_in * (denominator + g);
+            let discriminant =
+                b * b + U256::from(4) * g * denominator * U256::from(amount_in) * reserve_in;
+            let swap_amount = (discriminant.integer_sqrt() - b) / (g * 2);
+            Ok(swap_amount.as_u64().min(amount_in))
+        }
+        CurveType::StableSwap => {
+            // 교환 비율 q로 out = q·s라 하면 (a - s)/(q·s) = (x + s)/(y - q·s) 의 해는
+            // s = a·y / (y + q·(x + a)). q = 1로 s0를 구한 뒤 실제 q = out(s0)/s0 로 한 번 보정한다.
+            let guess = (amount_in as u128 * reserve_out as u128
+                / (reserve_in as u128 + reserve_out as u128 + amount_in as u128))
+                as u64;
+            if guess == 0 {
+                return Ok(0);
+            }
+            let guess_out = swap_amount_out(
+                curve_type,
+                amp,
+                guess,
+                reserve_in,
+                reserve_out,
+                fee_numerator,
+                fee_denominator,
+            )?;
+            let numerator = U256::from(amount_in) * U256::from(reserve_out) * U256::from(guess);
+            let denominator = U256::from(reserve_out) * U256::from(guess)
+                + U256::from(guess_out) * (U256::from(reserve_in) + U256::from(amount_in));
+            Ok((numerator / denominator).as_u64().min(amount_in))
+        }
+    }
+}
+
+/// flash swap 상환 검사: 입력분에서 수수료를 뺀 잔고의 불변량이 대여 전보다 작지 않아야 한다.
+/// 인자는 모두 (coin, pc) 순서.
+pub fn invariant_holds_after_fees(
+    curve_type: CurveType,
+    amp: u64,
+    reserves: (u64, u64),
+    balances: (u64, u64),
+    amounts_in: (u64, u64),
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<bool> {
+    let fee_numerator = fee_numerator as u128;
+    let fee_denominator = fee_denominator as u128;
+    match curve_type {
+        CurveType::ConstantProduct => {
+            // (x*den - in*fee) * (y*den - in*fee) >= x0 * y0 * den^2
+            let coin_adjusted =
+                balances.0 as u128 * fee_denominator - amounts_in.0 as u128 * fee_numerator;
+            let pc_adjusted =
+                balances.1 as u128 * fee_denominator - amounts_in.1 as u128 * fee_numerator;
+            Ok(U256::from(coin_adjusted) * U256::from(pc_adjusted)
+                >= U256::from(reserves.0)
+                    * U256::from(reserves.1)
+                    * U256::from(fee_denominator * fee_denominator))
+        }
+        CurveType::StableSwap => {
+            let adjusted = |balance: u64, amount_in: u64| {
+                balance as u128 - (amount_in as u128 * fee_numerator).div_ceil(fee_denominator)
+            };
+            let d_before = compute_d(amp, reserves.0 as u128, reserves.1 as u128)?;
+            let d_after = compute_d(
+                amp,
+                adjusted(balances.0, amounts_in.0),
+                adjusted(balances.1, amounts_in.1),
+            )?;
+            Ok(d_after >= d_before)
+        }
+    }
+}
+
+/// 잔고 (`coin_reserve`, `pc_reserve`)에서의 한계 가격 pc/coin을 (분자, 분모)로 돌려준다.
+/// constant product는 잔고 비율 그대로이고, StableSwap은 불변량 곡선의 기울기
+/// -dy/dx = (Ann * n^n * xy + D^3 / x) / (Ann * n^n * xy + D^3 / y) (x = coin, y = pc)이다.
+/// StableSwap 값은 비율을 유지한 채 SPOT_PRICE_BITS 비트 이하로 줄인다.
+pub fn spot_price(
+    curve_type: CurveType,
+    amp: u64,
+    coin_reserve: u64,
+    pc_reserve: u64,
+) -> Result<(U256, U256)> {
+    require!(
+        coin_reserve > 0 && pc_reserve > 0,
+        DexError::InsufficientLiquidity
+    );
+    match curve_type {
+        CurveType::ConstantProduct => Ok((U256::from(pc_reserve), U256::from(coin_reserve))),
+        CurveType::StableSwap => {
+            let d = U256::from(compute_d(amp, coin_reserve as u128, pc_reserve as u128)?);
+            let (x, y) = (U256::from(coin_reserve), U256::from(pc_reserve));
+            let ann_xy = U256::from(amp * N_COINS * N_COINS * N_COINS * N_COINS) * x * y;
+            let d_cubed = d * d * d;
+            let (numerator, denominator) = (ann_xy + d_cubed / x, ann_xy + d_cubed / y);
+            let shift = numerator
+                .bits()
+                .max(denominator.bits())
+                .saturating_sub(SPOT_PRICE_BITS);
+            Ok((numerator >> shift, (denominator >> shift).max(U256::one())))
+        }
+    }
+}
+
+/// 시간에 따라 선형으로 변하는 amplification 계수
+pub fn current_amp(
+    initial_amp: u64,
+    target_amp: u64,
+    ramp_start_timestamp: i64,
+    ramp_stop_timestamp: i64,
+    now: i64,
+) -> u64 {
+    if now >= ramp_stop_timestamp || ramp_stop_timestamp <= ramp_start_timestamp {
+        return target_amp;
+    }
+    if now <= ramp_start_timestamp {
+        return initial_amp;
+    }
+    let elapsed = (now - ramp_start_timestamp) as i128;
+    let duration = (ramp_stop_timestamp - ramp_start_timestamp) as i128;
+    let delta = target_amp as i128 - initial_amp as i128;
+    (initial_amp as i128 + delta * elapsed / duration) as u64
+}
+
+/// 불변량 D (Newton 반복). 잔고가 하나라도 0이면 0.
+pub fn compute_d(amp: u64, x: u128, y: u128) -> Result<u128> {
+    require!(amp >= MIN_AMP, DexError::InvalidAmp);
+    let sum = x + y;
+    if x == 0 || y == 0 {
+        return Ok(0);
+    }
+
+    let ann = U256::from(amp * N_COINS * N_COINS);
+    let n = U256::from(N_COINS);
+    let (x, y, sum) = (U256::from(x), U256::from(y), U256::from(sum));
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        // D_P = D^3 / (n^n * x * y) — 중간 나눗셈 없이 한 번에 (잔고 차이가 클 때 진동 방지)
+        let d_p = d * d * d / (x * y * n * n);
+        let previous = d;
+        d = (ann * sum + d_p * n) * d / ((ann - 1) * d + (n + 1) * d_p);
+        if d.abs_diff(previous) <= U256::one() {
+            return Ok(d.as_u128());
+        }
+    }
+    err!(DexError::CurveNotConverged)
+}
+
+/// 한쪽 잔고가 `x`일 때 불변량 `d`를 유지하는 반대쪽 잔고 (Newton 반복)
+pub fn compute_y(amp: u64, x: u128, d: u128) -> Result<u128> {
+    require!(amp >= MIN_AMP, DexError::InvalidAmp);
+    require!(x > 0, DexError::InsufficientLiquidity);
+
+    let ann = U256::from(amp * N_COINS * N_COINS);
+    let n = U256::from(N_COINS);
+    let (x, d) = (U256::from(x), U256::from(d));
+    // y^2 + (b - D) * y = c
+    let c = d * d * d / (x * n * ann * n);
+    let b = x + d / ann;
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let previous = y;
+        let denominator = (y + y + b).checked_sub(d).ok_or(DexError::MathOverflow)?;
+        y = (y * y + c) / denominator;
+        if y.abs_diff(previous) <= U256::one() {
+            return Ok(y.as_u128());
+        }
+    }
+    err!(DexError::CurveNotConverged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEE_NUMERATOR: u64 = 4;
+    const FEE_DENOMINATOR: u64 = 10_000;
+
+    #[test]
+    fn d_of_balanced_pool_is_sum() {
+        for amp in [1, 100, MAX_AMP] {
+            assert_eq!(compute_d(amp, 1_000_000, 1_000_000).unwrap(), 2_000_000);
+        }
+    }
+
+    #[test]
+    fn d_converges_for_imbalanced_pools() {
+        for amp in [MIN_AMP, 10, 100, 2_000, MAX_AMP] {
+            for (x, y) in [
+                (1u128, u64::MAX as u128),
+                (1_000, 1_000_000_000_000),
+                (123_456_789, 987_654_321),
+                (u64::MAX as u128, u64::MAX as u128 / 3),
+            ] {
+                let d = compute_d(amp, x, y).unwrap();
+                assert!(d > 0 && d <= x + y, "amp={amp} x={x} y={y} d={d}");
+                // D는 (반올림 오차 안에서) 대칭
+                assert!(d.abs_diff(compute_d(amp, y, x).unwrap()) <= 2);
+            }
+        }
+    }
+
+    #[test]
+    fn y_recovers_the_other_balance() {
+        for amp in [MIN_AMP, 100, 5_000] {
+            for (x, y) in [(1_000_000u128, 1_000_000u128), (5_000_000, 20_000_000)] {
+                let d = compute_d(amp, x, y).unwrap();
+                let recovered = compute_y(amp, x, d).unwrap();
+                assert!(
+                    recovered.abs_diff(y) <= 2,
+                    "amp={amp} y={y} got={recovered}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stable_swap_is_near_one_to_one_around_the_peg() {
+        let out = swap_amount_out(
+            CurveType::StableSwap,
+            100,
+            10_000,
+            1_000_000,
+            1_000_000,
+            FEE_NUMERATOR,
+            FEE_DENOMINATOR,
+        )
+        .unwrap();
+        let constant_product =
+            calculate_amount_out(10_000, 1_000_000, 1_000_000, FEE_NUMERATOR, FEE_DENOMINATOR)
+                .unwrap();
+        assert!(out > 9_980 && out < 10_000, "out={out}");
+        assert!(out > constant_product);
+    }
+
+    #[test]
+    fn stable_amount_in_covers_amount_out() {
+        for amount_out in [1, 1_000, 250_000, 900_000] {
+            let amount_in = swap_amount_in(
+                CurveType::StableSwap,
+                100,
+                amount_out,
+                1_000_000,
+                1_000_000,
+                FEE_NUMERATOR,
+                FEE_DENOMINATOR,
+            )
+            .unwrap();
+            let received = swap_amount_out(
+                CurveType::StableSwap,
+                100,
+                amount_in,
+                1_000_000,
+                1_000_000,
+                FEE_NUMERATOR,
+                FEE_DENOMINATOR,
+            )
+            .unwrap();
+            assert!(received >= amount_out, "in={amount_in} out={received}");
+        }
+    }
+
+    #[test]
+    fn swaps_never_decrease_d() {
+        let (x, y) = (3_000_000u64, 1_000_000u64);
+        let d_before = compute_d(50, x as u128, y as u128).unwrap();
+        let amount_out = swap_amount_out(
+            CurveType::StableSwap,
+            50,
+            500_000,
+            x,
+            y,
+            FEE_NUMERATOR,
+            FEE_DENOMINATOR,
+        )
+        .unwrap();
+        let d_after = compute_d(50, (x + 500_000) as u128, (y - amount_out) as u128).unwrap();
+        assert!(d_after >= d_before);
+    }
+
+    /// 스왑 후 남은 입력 / 받은 출력 비율과 스왑 후 풀 비율의 상대 오차
+    fn zap_ratio_error(
+        curve_type: CurveType,
+        amount_in: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+    ) -> f64 {
+        let swap_in = zap_swap_amount(
+            curve_type,
+            100,
+            amount_in,
+            reserve_in,
+            reserve_out,
+            30,
+            FEE_DENOMINATOR,
+        )
+        .unwrap();
+        let swap_out = swap_amount_out(
+            curve_type,
+            100,
+            swap_in,
+            reserve_in,
+            reserve_out,
+            30,
+            FEE_DENOMINATOR,
+        )
+        .unwrap();
+        let deposit_ratio = (amount_in - swap_in) as f64 / swap_out as f64;
+        let pool_ratio = (reserve_in + swap_in) as f64 / (reserve_out - swap_out) as f64;
+        (deposit_ratio / pool_ratio - 1.0).abs()
+    }
+
+    #[test]
+    fn zap_leaves_amounts_in_pool_ratio() {
+        for (amount_in, reserve_in, reserve_out) in [
+            (1_000_000, 10_000_000, 10_000_000),
+            (5_000_000, 10_000_000, 40_000_000),
+            (123_456_789, 987_654_321, 5_000_000_000),
+            (
+                1_000_000_000_000,
+                1_000_000_000_000_000,
+                1_000_000_000_000_000,
+            ),
+        ] {
+            let error = zap_ratio_error(
+                CurveType::ConstantProduct,
+                amount_in,
+                reserve_in,
+                reserve_out,
+            );
+            assert!(error < 1e-4, "cp a={amount_in} error={error}");
+        }
+        for (amount_in, reserve_in, reserve_out) in [
+            (1_000_000, 10_000_000, 10_000_000),
+            (1_000_000, 10_000_000, 12_000_000),
+            (100_000, 50_000_000, 30_000_000),
+        ] {
+            let error = zap_ratio_error(CurveType::StableSwap, amount_in, reserve_in, reserve_out);
+            assert!(error < 1e-3, "stable a={amount_in} error={error}");
+        }
+    }
+
+    fn price(curve_type: CurveType, amp: u64, coin: u64, pc: u64) -> f64 {
+        let (numerator, denominator) = spot_price(curve_type, amp, coin, pc).unwrap();
+        numerator.as_u128() as f64 / denominator.as_u128() as f64
+    }
+
+    #[test]
+    fn constant_product_spot_price_is_the_reserve_ratio() {
+        let (numerator, denominator) =
+            spot_price(CurveType::ConstantProduct, 0, 1_000, 3_000).unwrap();
+        assert_eq!((numerator.as_u64(), denominator.as_u64()), (3_000, 1_000));
+        assert!(spot_price(CurveType::ConstantProduct, 0, 0, 3_000).is_err());
+    }
+
+    #[test]
+    fn stable_spot_price_is_the_marginal_price() {
+        assert_eq!(price(CurveType::StableSwap, 100, 1_000_000, 1_000_000), 1.0);
+        for (amp, coin, pc) in [
+            (1, 1_000_000_000_000u64, 3_000_000_000_000u64),
+            (100, 1_000_000_000_000, 2_000_000_000_000),
+            (2_000, 7_000_000_000_000, 1_000_000_000_000),
+            (MAX_AMP, u64::MAX / 2, u64::MAX / 5),
+        ] {
+            // 아주 작은 스왑(수수료 없음)의 체결 가격과 비교
+            let step = coin / 1_000_000;
+            let d = compute_d(amp, coin as u128, pc as u128).unwrap();
+            let pc_after = compute_y(amp, (coin + step) as u128, d).unwrap();
+            let executed = (pc as u128 - pc_after) as f64 / step as f64;
+            let spot = price(CurveType::StableSwap, amp, coin, pc);
+            assert!(
+                (executed / spot - 1.0).abs() < 1e-5,
+                "amp={amp} spot={spot} executed={executed}"
+            );
+        }
+    }
+
+    #[test]
+    fn stable_spot_price_stays_near_the_peg() {
+        // 잔고 비율은 2지만 amp가 클수록 한계 가격은 1에 가깝다
+        let low = price(CurveType::StableSwap, 1, 1_000_000, 2_000_000);
+        let high = price(CurveType::StableSwap, 1_000, 1_000_000, 2_000_000);
+        assert!(
+            low > high && high > 1.0 && high < 1.01,
+            "low={low} high={high}"
+        );
+    }
+
+    #[test]
+    fn amp_ramps_linearly() {
+        assert_eq!(current_amp(100, 200, 1_000, 2_000, 500), 100);
+        assert_eq!(current_amp(100, 200, 1_000, 2_000, 1_500), 150);
+        assert_eq!(current_amp(200, 100, 1_000, 2_000, 1_250), 175);
+        assert_eq!(current_amp(100, 200, 1_000, 2_000, 3_000), 200);
+        assert_eq!(current_amp(100, 100, 0, 0, 42), 100);
+    }
+}
@@ -2,20 +2,110 @@
 
 // 필요한 라이브러리들을 import
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{
+    self, Burn, CloseAccount, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
+
+pub mod config;
+pub mod curve;
+pub mod math;
+pub mod transfer_fee;
+pub use config::{AmmConfig, AmmConfigParams, PoolEntry, PoolRegistry, POOLS_PER_PAGE};
+pub use curve::CurveType;
+use math::Rounding;
 
 // 프로그램 ID
 declare_id!("EsZBnxyMfSefm5SfHB53P5J3sA7fRCt94UgTQxCxJhK");
 
+/// 수수료 분모 (basis points)
+pub const FEE_DENOMINATOR: u64 = 10_000;
+/// 풀 생성 시 선택 가능한 수수료 티어 (bps): 스테이블 페어는 낮은 티어, 변동성 큰 페어는 높은 티어
+pub const FEE_TIERS_BPS: [u64; 4] = [1, 5, 30, 100];
+/// 이전 버전 풀의 고정 스왑 수수료 (bps) — `migrate_vaults`가 그대로 옮긴다
+pub const LEGACY_TRADE_FEE_BPS: u64 = 25;
+/// 첫 공급 시 영구히 잠그는 LP 수량 — LP 가격 부풀리기(donate-then-inflate) 공격 방지
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+/// 프로토콜 몫 분모: 스왑 수수료 중 `protocol_fee_numerator / PROTOCOL_FEE_DENOMINATOR` 만큼이 프로토콜 수익
+pub const PROTOCOL_FEE_DENOMINATOR: u64 = 10_000;
+/// 가격 밴드(`min/max_price_multiplier`)와 가격 영향 한도(`vol_max_cut_ratio`)의 분모
+pub const PRICE_MULTIPLIER_DENOMINATOR: u64 = 10_000;
+/// flash swap 콜백 instruction discriminator = sha256("global:flash_swap_callback")[..8].
+/// Anchor 수신 프로그램은 `flash_swap_callback` instruction을 정의하면 된다.
+pub const FLASH_SWAP_CALLBACK_DISCRIMINATOR: [u8; 8] = [225, 54, 80, 1, 45, 208, 202, 124];
+
+// 256비트 정수 — u128 곱이 넘칠 수 있는 불변량 비교용
+#[allow(clippy::all)]
+mod u256 {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+pub use u256::U256;
+
 #[program]
 pub mod solana_dex {
     use super::*;
 
-    pub fn initialize_pool(ctx: Context<InitializePool>, open_time: u64) -> Result<()> {
+    /// 전역 설정 생성. 배포 직후 프로그램 업그레이드 권한자가 한 번 호출하며, 호출자가 config admin이 된다.
+    pub fn initialize_amm_config(
+        ctx: Context<InitializeAmmConfig>,
+        params: AmmConfigParams,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.amm_config;
+        config.admin = ctx.accounts.admin.key();
+        config.pool_count = 0;
+        config.apply(&params)?;
+
+        msg!("Amm config initialized: {:?}", params);
+        Ok(())
+    }
+
+    /// 전역 설정 변경 (config admin). 이미 만들어진 풀에는 영향이 없다.
+    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, params: AmmConfigParams) -> Result<()> {
+        ctx.accounts.amm_config.apply(&params)?;
+
+        msg!("Amm config updated: {:?}", params);
+        Ok(())
+    }
+
+    /// 풀 생성. mint는 주소 오름차순(coin < pc)이어야 하므로 한 쌍에는 풀이 하나뿐이다.
+    /// `fee_bps`가 없으면 `AmmConfig`의 기본 티어를 쓰고, 생성된 풀은 레지스트리에 기록된다.
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        open_time: u64,
+        fee_bps: Option<u64>,
+        curve_type: CurveType,
+        amp: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.coin_mint.key() < ctx.accounts.pc_mint.key(),
+            DexError::MintsNotSorted
+        );
+        let fee_bps = fee_bps.unwrap_or(ctx.accounts.amm_config.default_trade_fee_bps);
+        require!(FEE_TIERS_BPS.contains(&fee_bps), DexError::InvalidFeeTier);
+        match curve_type {
+            CurveType::ConstantProduct => require!(amp == 0, DexError::InvalidAmp),
+            CurveType::StableSwap => {
+                require!(
+                    (curve::MIN_AMP..=curve::MAX_AMP).contains(&amp),
+                    DexError::InvalidAmp
+                );
+                // StableSwap은 두 토큰의 최소 단위가 같은 가치라고 가정한다
+                require!(
+                    ctx.accounts.coin_mint.decimals == ctx.accounts.pc_mint.decimals,
+                    DexError::DecimalsMismatch
+                );
+            }
+        }
+
         let pool = &mut ctx.accounts.amm_info;
 
         // 기본 상태 초기화 — 모든 주요 필드 명시적으로 설정
-        pool.status = 0;
+        // 생성 직후에는 유동성 공급만 허용, admin이 set_pool_status로 거래를 연다
+        pool.status = PoolStatus::DepositOnly as u64;
         pool.order_num = 0;
         pool.depth = 0;
 
@@ -46,8 +136,74 @@ pub mod solana_dex {
         pool.max_price_multiplier = 0;
         pool.sys_decimal_value = 0;
 
+        // 스왑 수수료
+        pool.trade_fee_numerator = fee_bps;
+        pool.trade_fee_denominator = FEE_DENOMINATOR;
+
+        // 팩토리 admin이 풀 admin — propose_admin/accept_admin으로 이전
+        pool.admin = ctx.accounts.amm_config.admin;
+        pool.pending_admin = Pubkey::default();
+
+        // 프로토콜 수수료 (AmmConfig 기본값 — admin이 set_protocol_fee로 변경)
+        pool.protocol_fee_numerator = ctx.accounts.amm_config.default_protocol_fee_numerator;
+        pool.protocol_fee_coin = 0;
+        pool.protocol_fee_pc = 0;
+
+        // 가격 오라클 (TWAP)
+        pool.price_coin_cumulative = 0;
+        pool.price_pc_cumulative = 0;
+        let now = Clock::get()?.unix_timestamp;
+        pool.last_update_timestamp = now;
+
+        // 스왑 곡선 (StableSwap만 amp 사용)
+        pool.curve_type = curve_type as u64;
+        pool.initial_amp = amp;
+        pool.target_amp = amp;
+        pool.ramp_start_timestamp = now;
+        pool.ramp_stop_timestamp = now;
+
         pool.amm_coin_account = ctx.accounts.coin_vault.key();
         pool.amm_pc_account = ctx.accounts.pc_vault.key();
+        pool.token_program = ctx.accounts.token_program.key();
+
+        // 레지스트리에 등록
+        let config = &mut ctx.accounts.amm_config;
+        config.register(
+            &mut ctx.accounts.pool_registry,
+            PoolEntry {
+                amm_info: pool.key(),
+                coin_mint: pool.coin_mint,
+                pc_mint: pool.pc_mint,
+                lp_mint: pool.lp_mint,
+            },
+        )?;
+
+        // 생성 수수료 (lamports)
+        if config.creation_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: ctx.accounts.fee_receiver.to_account_info(),
+                    },
+                ),
+                config.creation_fee,
+            )?;
+        }
+
+        emit!(PoolInitialized {
+            amm_info: pool.key(),
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            lp_mint: pool.lp_mint,
+            admin: pool.admin,
+            curve_type,
+            amp,
+            trade_fee_numerator: pool.trade_fee_numerator,
+            trade_fee_denominator: pool.trade_fee_denominator,
+            open_time,
+        });
 
         msg!(
             "Pool initialized with coin: {} and pc: {}",
@@ -62,66 +218,100 @@ pub mod solana_dex {
         max_coin_amount: u64,
         max_pc_amount: u64,
         base_side: u64,
+        deadline: Option<i64>,
     ) -> Result<()> {
         let pool = &mut ctx.accounts.amm_info;
+        require!(pool.pool_status()?.can_deposit(), DexError::PoolNotReady);
         let clock = Clock::get()?;
+        check_deadline(deadline, &clock)?;
 
         require!(
             clock.unix_timestamp >= pool.open_time as i64,
             DexError::PoolNotOpen
         );
 
-        let coin_reserve = ctx.accounts.coin_vault.amount;
-        let pc_reserve = ctx.accounts.pc_vault.amount;
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        pool.update_price_cumulative(coin_reserve, pc_reserve, clock.unix_timestamp);
 
-        let (deposit_coin, deposit_pc, mint_lp) = if coin_reserve == 0 && pc_reserve == 0 {
-            require!(max_coin_amount > 0 && max_pc_amount > 0, DexError::InvalidAmount);
-            let lp_amount = sqrt(max_coin_amount as u128 * max_pc_amount as u128)?;
-            (max_coin_amount, max_pc_amount, lp_amount as u64)
+        let lp_supply = ctx.accounts.lp_mint.supply;
+        // 첫 공급: MINIMUM_LIQUIDITY 만큼은 locked_lp_vault로 발행되어 영구히 잠긴다
+        let mut locked_lp = 0;
+
+        // 최대 지불량 중 transfer fee를 빼고 vault에 실제로 들어올 수 있는 양
+        let coin_mint = &ctx.accounts.coin_mint;
+        let pc_mint = &ctx.accounts.pc_mint;
+        let max_coin_received = transfer_fee::amount_after_fee(coin_mint, max_coin_amount)?;
+        let max_pc_received = transfer_fee::amount_after_fee(pc_mint, max_pc_amount)?;
+
+        let (deposit_coin, deposit_pc, mint_lp) = if lp_supply == 0 {
+            require!(
+                max_coin_received > 0 && max_pc_received > 0,
+                DexError::InvalidAmount
+            );
+            let lp_amount = curve::initial_liquidity(
+                pool.curve_type()?,
+                pool.amp(clock.unix_timestamp),
+                max_coin_received,
+                max_pc_received,
+            )?;
+            require!(
+                lp_amount > MINIMUM_LIQUIDITY,
+                DexError::InsufficientInitialLiquidity
+            );
+            locked_lp = MINIMUM_LIQUIDITY;
+            (
+                max_coin_received,
+                max_pc_received,
+                lp_amount - MINIMUM_LIQUIDITY,
+            )
         } else {
-            let lp_supply = ctx.accounts.lp_mint.supply;
+            require!(
+                coin_reserve > 0 && pc_reserve > 0,
+                DexError::InsufficientLiquidity
+            );
             let (coin_amount, pc_amount) = if base_side == 0 {
-                let pc_amount =
-                    (max_coin_amount as u128 * pc_reserve as u128 / coin_reserve as u128) as u64;
-                require!(pc_amount <= max_pc_amount, DexError::SlippageExceeded);
-                (max_coin_amount, pc_amount)
+                let pc_amount = math::paired_amount(max_coin_received, coin_reserve, pc_reserve)?;
+                require!(pc_amount <= max_pc_received, DexError::SlippageExceeded);
+                (max_coin_received, pc_amount)
             } else {
-                let coin_amount =
-                    (max_pc_amount as u128 * coin_reserve as u128 / pc_reserve as u128) as u64;
-                require!(coin_amount <= max_coin_amount, DexError::SlippageExceeded);
-                (coin_amount, max_pc_amount)
+                let coin_amount = math::paired_amount(max_pc_received, pc_reserve, coin_reserve)?;
+                require!(coin_amount <= max_coin_received, DexError::SlippageExceeded);
+                (coin_amount, max_pc_received)
             };
 
-            let lp_amount = std::cmp::min(
-                (coin_amount as u128 * lp_supply as u128 / coin_reserve as u128) as u64,
-                (pc_amount as u128 * lp_supply as u128 / pc_reserve as u128) as u64,
-            );
+            let lp_amount =
+                math::lp_for_deposit(coin_amount, pc_amount, coin_reserve, pc_reserve, lp_supply)?;
             (coin_amount, pc_amount, lp_amount)
         };
+        require!(mint_lp > 0, DexError::InvalidAmount);
 
-        // 사용자 → Vault
-        token::transfer(
+        // 사용자 → Vault (vault에 deposit_* 가 도착하도록 transfer fee만큼 더 보낸다)
+        token_interface::transfer_checked(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                TransferChecked {
                     from: ctx.accounts.user_coin_account.to_account_info(),
+                    mint: coin_mint.to_account_info(),
                     to: ctx.accounts.coin_vault.to_account_info(),
                     authority: ctx.accounts.user_authority.to_account_info(),
                 },
             ),
-            deposit_coin,
+            transfer_fee::amount_before_fee(coin_mint, deposit_coin)?,
+            coin_mint.decimals,
         )?;
 
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                TransferChecked {
                     from: ctx.accounts.user_pc_account.to_account_info(),
+                    mint: pc_mint.to_account_info(),
                     to: ctx.accounts.pc_vault.to_account_info(),
                     authority: ctx.accounts.user_authority.to_account_info(),
                 },
             ),
-            deposit_pc,
+            transfer_fee::amount_before_fee(pc_mint, deposit_pc)?,
+            pc_mint.decimals,
         )?;
 
         // LP mint_to (PDA signer)
@@ -133,9 +323,24 @@ pub mod solana_dex {
             pc_mint_key.as_ref(),
             &[ctx.bumps.amm_authority],
         ];
-        let signer = &[&seeds[..]];
-
-        token::mint_to(
+        let signer = &[seeds];
+
+        if locked_lp > 0 {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: ctx.accounts.lp_mint.to_account_info(),
+                        to: ctx.accounts.locked_lp_vault.to_account_info(),
+                        authority: ctx.accounts.amm_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                locked_lp,
+            )?;
+        }
+
+        token_interface::mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 MintTo {
@@ -148,10 +353,21 @@ pub mod solana_dex {
             mint_lp,
         )?;
 
-        // 풀 통계 갱신 (중요)
-        pool.pool_coin_amount = pool.pool_coin_amount.checked_add(deposit_coin).unwrap_or(pool.pool_coin_amount);
-        pool.pool_pc_amount = pool.pool_pc_amount.checked_add(deposit_pc).unwrap_or(pool.pool_pc_amount);
-        pool.pool_lp_amount = pool.pool_lp_amount.checked_add(mint_lp).unwrap_or(pool.pool_lp_amount);
+        // 풀 유동성 갱신
+        pool.add_liquidity(deposit_coin, deposit_pc, mint_lp + locked_lp)?;
+
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        ctx.accounts.lp_mint.reload()?;
+        emit!(LiquidityAdded {
+            amm_info: pool.key(),
+            user: ctx.accounts.user_authority.key(),
+            coin_amount: deposit_coin,
+            pc_amount: deposit_pc,
+            lp_amount: mint_lp,
+            coin_reserve,
+            pc_reserve,
+            lp_supply: ctx.accounts.lp_mint.supply,
+        });
 
         msg!(
             "Deposited: {} coin, {} pc, {} LP",
@@ -162,42 +378,228 @@ pub mod solana_dex {
         Ok(())
     }
 
+    /// 한쪽 토큰만으로 유동성을 공급한다 (zap). 입력 중 `curve::zap_swap_amount` 만큼을 풀 안에서
+    /// 반대쪽으로 스왑한 뒤, 남은 입력과 스왑 출력을 스왑 후 풀 비율로 공급한 것으로 보고 LP를 발행한다.
+    pub fn deposit_single_side(
+        ctx: Context<DepositSingleSide>,
+        amount_in: u64,
+        side: PoolSide,
+        min_lp_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        // 내부 스왑이 포함되므로 거래 가능한 상태여야 한다
+        require!(pool.pool_status()?.can_swap(), DexError::PoolNotReady);
+        let clock = Clock::get()?;
+        check_deadline(deadline, &clock)?;
+
+        let lp_supply = ctx.accounts.lp_mint.supply;
+        require!(lp_supply > 0, DexError::NoLiquidity);
+
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        require!(
+            coin_reserve > 0 && pc_reserve > 0,
+            DexError::InsufficientLiquidity
+        );
+        pool.update_price_cumulative(coin_reserve, pc_reserve, clock.unix_timestamp);
+
+        // side 토큰을 넣고 반대쪽을 받는 방향
+        let direction = side.swap_direction_from();
+        let (source_mint, _) = direction.orient(&ctx.accounts.coin_mint, &ctx.accounts.pc_mint);
+        let (source_vault, _) = direction.orient(&ctx.accounts.coin_vault, &ctx.accounts.pc_vault);
+        require_keys_eq!(
+            ctx.accounts.user_source_account.mint,
+            source_mint.key(),
+            DexError::InvalidUserTokenMint
+        );
+
+        let amount_received = transfer_fee::amount_after_fee(source_mint, amount_in)?;
+        let (reserve_in, reserve_out) = direction.orient(coin_reserve, pc_reserve);
+        let swap_in = curve::zap_swap_amount(
+            pool.curve_type()?,
+            pool.amp(clock.unix_timestamp),
+            amount_received,
+            reserve_in,
+            reserve_out,
+            pool.trade_fee_numerator,
+            pool.trade_fee_denominator,
+        )?;
+        let swap_out =
+            pool.swap_amount_out(swap_in, reserve_in, reserve_out, clock.unix_timestamp)?;
+        // 내부 스왑도 일반 스왑과 같은 체결 검사를 받는다
+        pool.check_trade(
+            direction,
+            swap_in,
+            swap_out,
+            coin_reserve,
+            pc_reserve,
+            clock.unix_timestamp,
+        )?;
+        let deposit_in = amount_received - swap_in;
+
+        // 스왑 후 유동성 (입력 쪽 프로토콜 수수료 제외) 기준으로 LP 계산
+        let reserve_in_after = reserve_in
+            .checked_add(swap_in - pool.protocol_fee(swap_in))
+            .ok_or(DexError::MathOverflow)?;
+        let (deposit_coin, deposit_pc) = direction.orient(deposit_in, swap_out);
+        let (coin_reserve_after, pc_reserve_after) =
+            direction.orient(reserve_in_after, reserve_out - swap_out);
+        let mint_lp = math::lp_for_deposit(
+            deposit_coin,
+            deposit_pc,
+            coin_reserve_after,
+            pc_reserve_after,
+            lp_supply,
+        )?;
+        require!(mint_lp > 0, DexError::InvalidAmount);
+        require!(mint_lp >= min_lp_out, DexError::SlippageExceeded);
+
+        // 사용자 → source_vault (입력 전체)
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_source_account.to_account_info(),
+                    mint: source_mint.to_account_info(),
+                    to: source_vault.to_account_info(),
+                    authority: ctx.accounts.user_authority.to_account_info(),
+                },
+            ),
+            amount_in,
+            source_mint.decimals,
+        )?;
+
+        // LP mint_to (PDA signer)
+        let coin_mint_key = ctx.accounts.coin_mint.key();
+        let pc_mint_key = ctx.accounts.pc_mint.key();
+        let seeds: &[&[u8]] = &[
+            b"amm_authority",
+            coin_mint_key.as_ref(),
+            pc_mint_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[seeds];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.user_lp_account.to_account_info(),
+                    authority: ctx.accounts.amm_authority.to_account_info(),
+                },
+                signer,
+            ),
+            mint_lp,
+        )?;
+
+        // 풀 통계 갱신: 내부 스왑 반영 후 (남은 입력, 스왑 출력)을 공급분으로 더한다
+        pool.apply_swap(direction, swap_in, swap_out)?;
+        pool.check_price_impact(coin_reserve, pc_reserve, clock.unix_timestamp)?;
+        pool.add_liquidity(deposit_coin, deposit_pc, mint_lp)?;
+
+        // 내부 스왑은 풀 안에서 끝나므로 사용자가 넣은 양은 입력 쪽에만 기록한다
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        ctx.accounts.lp_mint.reload()?;
+        let (coin_amount, pc_amount) = direction.orient(amount_received, 0);
+        emit!(LiquidityAdded {
+            amm_info: pool.key(),
+            user: ctx.accounts.user_authority.key(),
+            coin_amount,
+            pc_amount,
+            lp_amount: mint_lp,
+            coin_reserve,
+            pc_reserve,
+            lp_supply: ctx.accounts.lp_mint.supply,
+        });
+
+        msg!(
+            "Deposited single side: {} ({:?}, {} swapped) for {} LP",
+            amount_in,
+            side,
+            swap_in,
+            mint_lp
+        );
+        Ok(())
+    }
+
     pub fn swap_base_in(
         ctx: Context<SwapBaseIn>,
         amount_in: u64,
         minimum_amount_out: u64,
+        direction: SwapDirection,
+        deadline: Option<i64>,
     ) -> Result<()> {
-        let pool = &ctx.accounts.amm_info;
-        require!(pool.status == 3, DexError::PoolNotReady);
+        let pool = &mut ctx.accounts.amm_info;
+        require!(pool.pool_status()?.can_swap(), DexError::PoolNotReady);
+        let clock = Clock::get()?;
+        check_deadline(deadline, &clock)?;
 
-        let source_reserve = ctx.accounts.source_vault.amount;
-        let destination_reserve = ctx.accounts.destination_vault.amount;
+        // 입력은 lot 단위로 내림 — 나머지는 전송하지 않으므로 `user_coin/pc_account`에 남는다.
+        // router처럼 중간 계정을 쓰는 호출자는 미리 lot 단위로 맞추고 나머지를 처리해야 한다
+        let (source_lot_size, _) = pool.lot_sizes(direction);
+        let amount_in = amount_in - amount_in % source_lot_size;
+        require!(amount_in > 0, DexError::InvalidAmount);
 
+        let (coin_reserve, pc_reserve) = pool.reserves();
         require!(
-            source_reserve > 0 && destination_reserve > 0,
+            coin_reserve > 0 && pc_reserve > 0,
             DexError::InsufficientLiquidity
         );
 
-        let amount_out = calculate_amount_out(
-            amount_in,
+        // 오라클 갱신 (스왑 전 유동성 기준)
+        pool.update_price_cumulative(coin_reserve, pc_reserve, clock.unix_timestamp);
+
+        let (source_mint, destination_mint) =
+            direction.orient(&ctx.accounts.coin_mint, &ctx.accounts.pc_mint);
+        // transfer fee를 뗀, vault에 실제로 들어오는 양으로 견적
+        let amount_received = transfer_fee::amount_after_fee(source_mint, amount_in)?;
+        let (source_reserve, destination_reserve) = direction.orient(coin_reserve, pc_reserve);
+        let amount_out = pool.swap_amount_out(
+            amount_received,
             source_reserve,
             destination_reserve,
-            pool.min_size,
+            clock.unix_timestamp,
+        )?;
+        pool.check_trade(
+            direction,
+            amount_received,
+            amount_out,
+            coin_reserve,
+            pc_reserve,
+            clock.unix_timestamp,
         )?;
 
-        require!(amount_out >= minimum_amount_out, DexError::SlippageExceeded);
+        require!(
+            transfer_fee::amount_after_fee(destination_mint, amount_out)? >= minimum_amount_out,
+            DexError::SlippageExceeded
+        );
+
+        // vault와 사용자 계정은 방향에 따라 AmmInfo에 묶인 계정들 중에서 결정
+        let (source_vault, destination_vault) =
+            direction.orient(&ctx.accounts.coin_vault, &ctx.accounts.pc_vault);
+        let (user_source_account, user_destination_account) = direction.orient(
+            &ctx.accounts.user_coin_account,
+            &ctx.accounts.user_pc_account,
+        );
+        require!(
+            user_source_account.owner == ctx.accounts.user_authority.key(),
+            DexError::InvalidUserTokenOwner
+        );
 
         // 사용자 → source_vault
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.user_source_account.to_account_info(),
-                    to: ctx.accounts.source_vault.to_account_info(),
+                TransferChecked {
+                    from: user_source_account.to_account_info(),
+                    mint: source_mint.to_account_info(),
+                    to: source_vault.to_account_info(),
                     authority: ctx.accounts.user_authority.to_account_info(),
                 },
             ),
             amount_in,
+            source_mint.decimals,
         )?;
 
         // PDA signer (amm_authority)
@@ -209,41 +611,200 @@ pub mod solana_dex {
             pc_mint_key.as_ref(),
             &[ctx.bumps.amm_authority],
         ];
-        let signer = &[&seeds[..]];
+        let signer = &[seeds];
 
         // pool → 사용자 (destination)
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.destination_vault.to_account_info(),
-                    to: ctx.accounts.user_destination_account.to_account_info(),
+                TransferChecked {
+                    from: destination_vault.to_account_info(),
+                    mint: destination_mint.to_account_info(),
+                    to: user_destination_account.to_account_info(),
                     authority: ctx.accounts.amm_authority.to_account_info(),
                 },
                 signer,
             ),
             amount_out,
+            destination_mint.decimals,
         )?;
 
-        // (선택) 풀 통계 갱신: pool.pool_coin_amount/pc_amount 등 (생략 가능 — 구현 필요시 추가)
+        // 프로토콜 수수료 적립 + 풀 통계 갱신
+        pool.apply_swap(direction, amount_received, amount_out)?;
+        pool.check_price_impact(coin_reserve, pc_reserve, clock.unix_timestamp)?;
+
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        emit!(Swap {
+            amm_info: pool.key(),
+            user: ctx.accounts.user_authority.key(),
+            direction,
+            amount_in: amount_received,
+            amount_out,
+            coin_reserve,
+            pc_reserve,
+        });
+
         msg!("Swapped {} for {}", amount_in, amount_out);
         Ok(())
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    pub fn swap_base_out(
+        ctx: Context<SwapBaseOut>,
+        amount_out: u64,
+        max_amount_in: u64,
+        direction: SwapDirection,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        require!(pool.pool_status()?.can_swap(), DexError::PoolNotReady);
+        let clock = Clock::get()?;
+        check_deadline(deadline, &clock)?;
+
+        // 출력은 lot 단위로 올림 — 요청보다 적게 받는 일은 없다
+        let (_, destination_lot_size) = pool.lot_sizes(direction);
+        let amount_out = amount_out
+            .div_ceil(destination_lot_size)
+            .checked_mul(destination_lot_size)
+            .ok_or(DexError::MathOverflow)?;
+
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        require!(
+            coin_reserve > 0 && pc_reserve > 0,
+            DexError::InsufficientLiquidity
+        );
+
+        // 오라클 갱신 (스왑 전 유동성 기준)
+        pool.update_price_cumulative(coin_reserve, pc_reserve, clock.unix_timestamp);
+
+        let (source_mint, destination_mint) =
+            direction.orient(&ctx.accounts.coin_mint, &ctx.accounts.pc_mint);
+        // 사용자에게 정확히 amount_out이 도착하도록 vault에서 보낼 양과,
+        // 그만큼을 받기 위해 vault에 들어와야 하는 양 / 사용자가 보내야 하는 양
+        let vault_amount_out = transfer_fee::amount_before_fee(destination_mint, amount_out)?;
+        let (source_reserve, destination_reserve) = direction.orient(coin_reserve, pc_reserve);
+        let amount_received = pool.swap_amount_in(
+            vault_amount_out,
+            source_reserve,
+            destination_reserve,
+            clock.unix_timestamp,
+        )?;
+        let amount_in = transfer_fee::amount_before_fee(source_mint, amount_received)?;
+        pool.check_trade(
+            direction,
+            amount_received,
+            vault_amount_out,
+            coin_reserve,
+            pc_reserve,
+            clock.unix_timestamp,
+        )?;
+
+        require!(amount_in <= max_amount_in, DexError::SlippageExceeded);
+
+        // vault와 사용자 계정은 방향에 따라 AmmInfo에 묶인 계정들 중에서 결정
+        let (source_vault, destination_vault) =
+            direction.orient(&ctx.accounts.coin_vault, &ctx.accounts.pc_vault);
+        let (user_source_account, user_destination_account) = direction.orient(
+            &ctx.accounts.user_coin_account,
+            &ctx.accounts.user_pc_account,
+        );
+        require!(
+            user_source_account.owner == ctx.accounts.user_authority.key(),
+            DexError::InvalidUserTokenOwner
+        );
+
+        // 사용자 → source_vault
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: user_source_account.to_account_info(),
+                    mint: source_mint.to_account_info(),
+                    to: source_vault.to_account_info(),
+                    authority: ctx.accounts.user_authority.to_account_info(),
+                },
+            ),
+            amount_in,
+            source_mint.decimals,
+        )?;
+
+        // PDA signer (amm_authority)
+        let coin_mint_key = ctx.accounts.coin_mint.key();
+        let pc_mint_key = ctx.accounts.pc_mint.key();
+        let seeds: &[&[u8]] = &[
+            b"amm_authority",
+            coin_mint_key.as_ref(),
+            pc_mint_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[seeds];
+
+        // pool → 사용자 (destination)
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: destination_vault.to_account_info(),
+                    mint: destination_mint.to_account_info(),
+                    to: user_destination_account.to_account_info(),
+                    authority: ctx.accounts.amm_authority.to_account_info(),
+                },
+                signer,
+            ),
+            vault_amount_out,
+            destination_mint.decimals,
+        )?;
+
+        // 프로토콜 수수료 적립 + 풀 통계 갱신
+        pool.apply_swap(direction, amount_received, vault_amount_out)?;
+        pool.check_price_impact(coin_reserve, pc_reserve, clock.unix_timestamp)?;
+
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        emit!(Swap {
+            amm_info: pool.key(),
+            user: ctx.accounts.user_authority.key(),
+            direction,
+            amount_in: amount_received,
+            amount_out: vault_amount_out,
+            coin_reserve,
+            pc_reserve,
+        });
+
+        msg!("Swapped {} for exactly {}", amount_in, amount_out);
+        Ok(())
+    }
+
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        amount: u64,
+        min_coin_amount: u64,
+        min_pc_amount: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
         let pool = &mut ctx.accounts.amm_info;
+        require!(pool.pool_status()?.can_withdraw(), DexError::PoolNotReady);
+        let clock = Clock::get()?;
+        check_deadline(deadline, &clock)?;
 
         let lp_supply = ctx.accounts.lp_mint.supply;
         require!(lp_supply > 0, DexError::NoLiquidity);
 
-        let coin_reserve = ctx.accounts.coin_vault.amount;
-        let pc_reserve = ctx.accounts.pc_vault.amount;
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        pool.update_price_cumulative(coin_reserve, pc_reserve, clock.unix_timestamp);
 
-        let withdraw_coin = (coin_reserve as u128 * amount as u128 / lp_supply as u128) as u64;
-        let withdraw_pc = (pc_reserve as u128 * amount as u128 / lp_supply as u128) as u64;
+        let (withdraw_coin, withdraw_pc) =
+            math::withdraw_amounts(amount, coin_reserve, pc_reserve, lp_supply)?;
+
+        // 최소 수령량은 transfer fee를 뗀, 사용자에게 실제로 도착하는 양과 비교
+        let coin_mint = &ctx.accounts.coin_mint;
+        let pc_mint = &ctx.accounts.pc_mint;
+        require!(
+            transfer_fee::amount_after_fee(coin_mint, withdraw_coin)? >= min_coin_amount
+                && transfer_fee::amount_after_fee(pc_mint, withdraw_pc)? >= min_pc_amount,
+            DexError::SlippageExceeded
+        );
 
         // 사용자의 LP 소각
-        token::burn(
+        token_interface::burn(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
                 Burn {
@@ -264,40 +825,55 @@ pub mod solana_dex {
             pc_mint_key.as_ref(),
             &[ctx.bumps.amm_authority],
         ];
-        let signer = &[&signer_seeds[..]];
+        let signer = &[signer_seeds];
 
         // token 반환 (coin)
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                TransferChecked {
                     from: ctx.accounts.coin_vault.to_account_info(),
+                    mint: coin_mint.to_account_info(),
                     to: ctx.accounts.user_coin_account.to_account_info(),
                     authority: ctx.accounts.amm_authority.to_account_info(),
                 },
                 signer,
             ),
             withdraw_coin,
+            coin_mint.decimals,
         )?;
 
         // token 반환 (pc)
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                TransferChecked {
                     from: ctx.accounts.pc_vault.to_account_info(),
+                    mint: pc_mint.to_account_info(),
                     to: ctx.accounts.user_pc_account.to_account_info(),
                     authority: ctx.accounts.amm_authority.to_account_info(),
                 },
                 signer,
             ),
             withdraw_pc,
+            pc_mint.decimals,
         )?;
 
         // 풀 통계 갱신
-        pool.pool_coin_amount = pool.pool_coin_amount.saturating_sub(withdraw_coin);
-        pool.pool_pc_amount = pool.pool_pc_amount.saturating_sub(withdraw_pc);
-        pool.pool_lp_amount = pool.pool_lp_amount.saturating_sub(amount);
+        pool.remove_liquidity(withdraw_coin, withdraw_pc, amount)?;
+
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        ctx.accounts.lp_mint.reload()?;
+        emit!(LiquidityRemoved {
+            amm_info: pool.key(),
+            user: ctx.accounts.user_authority.key(),
+            lp_amount: amount,
+            coin_amount: withdraw_coin,
+            pc_amount: withdraw_pc,
+            coin_reserve,
+            pc_reserve,
+            lp_supply: ctx.accounts.lp_mint.supply,
+        });
 
         msg!(
             "Withdrawn: {} coin, {} pc for {} LP",
@@ -307,232 +883,2077 @@ pub mod solana_dex {
         );
         Ok(())
     }
-}
 
-// =========== Helper, Accounts, Errors (same as before, 단 LEN 수정) ===========
+    /// LP를 소각하고 한쪽 토큰으로만 받는다. 반대쪽 몫은 풀 안에서 (출금 후 유동성 기준으로) 스왑된다.
+    pub fn withdraw_single_side(
+        ctx: Context<Withdraw>,
+        amount: u64,
+        side: PoolSide,
+        min_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        // 내부 스왑이 포함되므로 거래 가능한 상태여야 한다
+        require!(pool.pool_status()?.can_swap(), DexError::PoolNotReady);
+        let clock = Clock::get()?;
+        check_deadline(deadline, &clock)?;
 
-pub fn calculate_amount_out(
-    amount_in: u64,
-    reserve_in: u64,
-    reserve_out: u64,
-    _fee_numerator: u64,
-) -> Result<u64> {
-    require!(amount_in > 0, DexError::InvalidAmount);
-    require!(reserve_in > 0 && reserve_out > 0, DexError::InsufficientLiquidity);
+        let lp_supply = ctx.accounts.lp_mint.supply;
+        require!(lp_supply > 0, DexError::NoLiquidity);
 
-    let amount_in_with_fee = (amount_in as u128) * 9975;
-    let numerator = amount_in_with_fee * (reserve_out as u128);
-    let denominator = (reserve_in as u128) * 10000 + amount_in_with_fee;
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        pool.update_price_cumulative(coin_reserve, pc_reserve, clock.unix_timestamp);
 
-    Ok((numerator / denominator) as u64)
-}
+        let (withdraw_coin, withdraw_pc) =
+            math::withdraw_amounts(amount, coin_reserve, pc_reserve, lp_supply)?;
 
-pub fn sqrt(y: u128) -> Result<u64> {
-    if y == 0 {
-        return Ok(0);
-    }
-    let mut z = y;
-    let mut x = y / 2 + 1;
-    while x < z {
-        z = x;
-        x = (y / x + x) / 2;
-    }
-    Ok(z as u64)
-}
+        // 반대쪽 몫을 출금 후 유동성에 대해 원하는 쪽으로 스왑
+        let direction = side.swap_direction_into();
+        let (swap_in, kept_out) = direction.orient(withdraw_coin, withdraw_pc);
+        let (coin_remaining, pc_remaining) =
+            (coin_reserve - withdraw_coin, pc_reserve - withdraw_pc);
+        let (source_reserve, destination_reserve) = direction.orient(coin_remaining, pc_remaining);
+        let swap_out = pool.swap_amount_out(
+            swap_in,
+            source_reserve,
+            destination_reserve,
+            clock.unix_timestamp,
+        )?;
+        // 내부 스왑도 일반 스왑과 같은 체결 검사를 받는다
+        pool.check_trade(
+            direction,
+            swap_in,
+            swap_out,
+            coin_remaining,
+            pc_remaining,
+            clock.unix_timestamp,
+        )?;
+        let amount_out = kept_out
+            .checked_add(swap_out)
+            .ok_or(DexError::MathOverflow)?;
 
-#[account]
-pub struct AmmInfo {
-    pub status: u64,
-    // pub nonce: u64, // 제거
-    pub order_num: u64,
-    pub depth: u64,
+        let (_, mint) = direction.orient(&ctx.accounts.coin_mint, &ctx.accounts.pc_mint);
+        require!(
+            transfer_fee::amount_after_fee(mint, amount_out)? >= min_amount_out,
+            DexError::SlippageExceeded
+        );
 
-    pub coin_mint: Pubkey,
-    pub pc_mint: Pubkey,
+        // 사용자의 LP 소각
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    from: ctx.accounts.user_lp_account.to_account_info(),
+                    authority: ctx.accounts.user_authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
 
-    pub coin_vault: Pubkey,
-    pub pc_vault: Pubkey,
+        // PDA signer (amm_authority)
+        let coin_mint_key = ctx.accounts.coin_mint.key();
+        let pc_mint_key = ctx.accounts.pc_mint.key();
+        let signer_seeds: &[&[u8]] = &[
+            b"amm_authority",
+            coin_mint_key.as_ref(),
+            pc_mint_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[signer_seeds];
 
-    pub lp_mint: Pubkey,
+        // token 반환 (원하는 쪽만)
+        let (_, vault) = direction.orient(&ctx.accounts.coin_vault, &ctx.accounts.pc_vault);
+        let (_, user_account) = direction.orient(
+            &ctx.accounts.user_coin_account,
+            &ctx.accounts.user_pc_account,
+        );
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: user_account.to_account_info(),
+                    authority: ctx.accounts.amm_authority.to_account_info(),
+                },
+                signer,
+            ),
+            amount_out,
+            mint.decimals,
+        )?;
 
-    pub open_time: u64,
+        // 풀 통계 갱신: 비례 출금 후 내부 스왑 반영
+        pool.remove_liquidity(withdraw_coin, withdraw_pc, amount)?;
+        pool.apply_swap(direction, swap_in, swap_out)?;
+        pool.check_price_impact(coin_remaining, pc_remaining, clock.unix_timestamp)?;
 
-    pub punish_coin_amount: u64,
-    pub punish_pc_amount: u64,
+        let (coin_amount, pc_amount) = match side {
+            PoolSide::Coin => (amount_out, 0),
+            PoolSide::Pc => (0, amount_out),
+        };
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        ctx.accounts.lp_mint.reload()?;
+        emit!(LiquidityRemoved {
+            amm_info: pool.key(),
+            user: ctx.accounts.user_authority.key(),
+            lp_amount: amount,
+            coin_amount,
+            pc_amount,
+            coin_reserve,
+            pc_reserve,
+            lp_supply: ctx.accounts.lp_mint.supply,
+        });
 
-    pub pool_coin_amount: u64,
-    pub pool_pc_amount: u64,
-    pub pool_lp_amount: u64,
+        msg!(
+            "Withdrawn single side: {} ({:?}) for {} LP",
+            amount_out,
+            side,
+            amount
+        );
+        Ok(())
+    }
 
-    pub min_size: u64,
-    pub vol_max_cut_ratio: u64,
-    pub amount_wave_ratio: u64,
-    pub coin_lot_size: u64,
-    pub pc_lot_size: u64,
+    /// vault 재고를 한 instruction 동안 빌려준다 (Uniswap v2 방식 flash swap).
+    ///
+    /// 요청한 수량을 receiver 계정으로 먼저 보낸 뒤 `callback_program`의
+    /// `flash_swap_callback`을 호출한다 (`FlashSwapCallbackArgs` 참고). 콜백의 첫 계정은
+    /// amm_authority이고, 나머지는 remaining accounts가 순서대로 전달된다. amm_authority의
+    /// 서명은 콜백에 넘기지 않으므로 콜백은 vault나 LP mint를 움직일 수 없다.
+    /// 콜백이 끝난 뒤 수수료를 반영한 x*y가 빌리기 전보다 작거나, LP 공급량 또는 잠긴 LP가
+    /// 바뀌었으면 전체가 실패한다.
+    /// 상환은 vault 잔고로 검사하므로 transfer fee가 있는 mint는 그만큼 더 보내야 한다.
+    /// 콜백에서 ammv2로의 재진입은 런타임이 막는다 (자기 자신은 콜백으로 지정 불가).
+    pub fn flash_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlashSwap<'info>>,
+        coin_amount_out: u64,
+        pc_amount_out: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        require!(pool.pool_status()?.can_swap(), DexError::PoolNotReady);
+        require!(
+            coin_amount_out > 0 || pc_amount_out > 0,
+            DexError::InvalidAmount
+        );
+        require!(
+            ctx.accounts.callback_program.key() != crate::ID,
+            DexError::InvalidCallbackProgram
+        );
+        let clock = Clock::get()?;
 
-    pub min_price_multiplier: u64,
-    pub max_price_multiplier: u64,
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        require!(
+            coin_amount_out < coin_reserve && pc_amount_out < pc_reserve,
+            DexError::InsufficientLiquidity
+        );
 
-    pub sys_decimal_value: u64,
+        // 오라클 갱신 (대여 전 유동성 기준)
+        pool.update_price_cumulative(coin_reserve, pc_reserve, clock.unix_timestamp);
+
+        // 콜백 전 LP 상태 — 콜백 후 그대로여야 한다
+        let lp_supply = ctx.accounts.lp_mint.supply;
+        let locked_lp = ctx.accounts.locked_lp_vault.amount;
+
+        // PDA signer (amm_authority)
+        let coin_mint_key = ctx.accounts.coin_mint.key();
+        let pc_mint_key = ctx.accounts.pc_mint.key();
+        let seeds: &[&[u8]] = &[
+            b"amm_authority",
+            coin_mint_key.as_ref(),
+            pc_mint_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[seeds];
+
+        // pool → receiver (상환 전에 먼저 지급)
+        for (vault, mint, receiver, amount) in [
+            (
+                &ctx.accounts.coin_vault,
+                &ctx.accounts.coin_mint,
+                &ctx.accounts.receiver_coin_account,
+                coin_amount_out,
+            ),
+            (
+                &ctx.accounts.pc_vault,
+                &ctx.accounts.pc_mint,
+                &ctx.accounts.receiver_pc_account,
+                pc_amount_out,
+            ),
+        ] {
+            if amount > 0 {
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        TransferChecked {
+                            from: vault.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: receiver.to_account_info(),
+                            authority: ctx.accounts.amm_authority.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                    mint.decimals,
+                )?;
+            }
+        }
+
+        // 콜백 호출: [amm_authority(서명 없음), ...remaining_accounts]
+        let args = FlashSwapCallbackArgs {
+            initiator: ctx.accounts.initiator.key(),
+            coin_amount: coin_amount_out,
+            pc_amount: pc_amount_out,
+            coin_fee: calculate_flash_fee(
+                coin_amount_out,
+                pool.trade_fee_numerator,
+                pool.trade_fee_denominator,
+            )?,
+            pc_fee: calculate_flash_fee(
+                pc_amount_out,
+                pool.trade_fee_numerator,
+                pool.trade_fee_denominator,
+            )?,
+            data,
+        };
+        let mut ix_data = FLASH_SWAP_CALLBACK_DISCRIMINATOR.to_vec();
+        args.serialize(&mut ix_data)?;
+
+        let mut metas = vec![AccountMeta::new_readonly(
+            ctx.accounts.amm_authority.key(),
+            false,
+        )];
+        let mut infos = vec![ctx.accounts.amm_authority.to_account_info()];
+        for account in ctx.remaining_accounts {
+            metas.push(if account.is_writable {
+                AccountMeta::new(account.key(), account.is_signer)
+            } else {
+                AccountMeta::new_readonly(account.key(), account.is_signer)
+            });
+            infos.push(account.clone());
+        }
+        infos.push(ctx.accounts.callback_program.to_account_info());
+
+        invoke(
+            &Instruction {
+                program_id: ctx.accounts.callback_program.key(),
+                accounts: metas,
+                data: ix_data,
+            },
+            &infos,
+        )?;
+
+        ctx.accounts.lp_mint.reload()?;
+        ctx.accounts.locked_lp_vault.reload()?;
+        require!(
+            ctx.accounts.lp_mint.supply == lp_supply
+                && ctx.accounts.locked_lp_vault.amount == locked_lp,
+            DexError::FlashSwapLpChanged
+        );
+
+        // 상환 검사: 수수료를 뗀 잔고 기준 불변량이 줄지 않아야 한다
+        ctx.accounts.coin_vault.reload()?;
+        ctx.accounts.pc_vault.reload()?;
+        let (coin_balance, pc_balance) =
+            pool.vault_liquidity(ctx.accounts.coin_vault.amount, ctx.accounts.pc_vault.amount)?;
+        let coin_in = coin_balance.saturating_sub(coin_reserve - coin_amount_out);
+        let pc_in = pc_balance.saturating_sub(pc_reserve - pc_amount_out);
+        require!(
+            curve::invariant_holds_after_fees(
+                pool.curve_type()?,
+                pool.amp(clock.unix_timestamp),
+                (coin_reserve, pc_reserve),
+                (coin_balance, pc_balance),
+                (coin_in, pc_in),
+                pool.trade_fee_numerator,
+                pool.trade_fee_denominator,
+            )?,
+            DexError::FlashSwapNotRepaid
+        );
+        // 순 흐름이 한쪽으로 들어와 반대쪽으로 나갔으면 그만큼의 스왑으로 보고 검사한다
+        if let Some((direction, amount_in, amount_out)) =
+            flash_net_swap((coin_amount_out, pc_amount_out), (coin_in, pc_in))
+        {
+            pool.check_trade(
+                direction,
+                amount_in,
+                amount_out,
+                coin_reserve,
+                pc_reserve,
+                clock.unix_timestamp,
+            )?;
+        }
+
+        // 입력된 쪽에 프로토콜 수수료 적립, 풀 유동성은 상환 후 vault 잔고로 맞춘다 (Uniswap v2처럼
+        // 대여 전 vault에 남아 있던 잉여분도 함께 편입된다)
+        let coin_protocol_fee = pool.protocol_fee(coin_in);
+        let pc_protocol_fee = pool.protocol_fee(pc_in);
+        pool.protocol_fee_coin = pool
+            .protocol_fee_coin
+            .checked_add(coin_protocol_fee)
+            .ok_or(DexError::MathOverflow)?;
+        pool.protocol_fee_pc = pool
+            .protocol_fee_pc
+            .checked_add(pc_protocol_fee)
+            .ok_or(DexError::MathOverflow)?;
+        pool.pool_coin_amount = coin_balance - coin_protocol_fee;
+        pool.pool_pc_amount = pc_balance - pc_protocol_fee;
+        pool.check_price_impact(coin_reserve, pc_reserve, clock.unix_timestamp)?;
+
+        msg!(
+            "Flash swapped out {} coin, {} pc; repaid {} coin, {} pc",
+            coin_amount_out,
+            pc_amount_out,
+            coin_in,
+            pc_in
+        );
+        Ok(())
+    }
+
+    /// StableSwap 풀의 amp를 현재 값에서 `target_amp`까지 `ramp_stop_timestamp`에 걸쳐 선형으로 바꾼다.
+    /// 한 번에 MAX_AMP_CHANGE 배까지, 최소 MIN_RAMP_DURATION초 동안.
+    pub fn ramp_amp(
+        ctx: Context<RampAmp>,
+        target_amp: u64,
+        ramp_stop_timestamp: i64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        require!(
+            pool.curve_type()? == CurveType::StableSwap,
+            DexError::InvalidCurveType
+        );
+        require!(
+            (curve::MIN_AMP..=curve::MAX_AMP).contains(&target_amp),
+            DexError::InvalidAmp
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ramp_stop_timestamp >= now.saturating_add(curve::MIN_RAMP_DURATION),
+            DexError::InvalidRamp
+        );
+
+        let current_amp = pool.amp(now);
+        require!(
+            target_amp <= current_amp * curve::MAX_AMP_CHANGE
+                && target_amp * curve::MAX_AMP_CHANGE >= current_amp,
+            DexError::InvalidRamp
+        );
+
+        pool.initial_amp = current_amp;
+        pool.target_amp = target_amp;
+        pool.ramp_start_timestamp = now;
+        pool.ramp_stop_timestamp = ramp_stop_timestamp;
+
+        msg!(
+            "Ramping amp {} -> {} until {}",
+            current_amp,
+            target_amp,
+            ramp_stop_timestamp
+        );
+        Ok(())
+    }
+
+    /// 진행 중인 ramp를 현재 amp에서 멈춘다.
+    pub fn stop_ramp_amp(ctx: Context<StopRampAmp>) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        require!(
+            pool.curve_type()? == CurveType::StableSwap,
+            DexError::InvalidCurveType
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let current_amp = pool.amp(now);
+
+        pool.initial_amp = current_amp;
+        pool.target_amp = current_amp;
+        pool.ramp_start_timestamp = now;
+        pool.ramp_stop_timestamp = now;
+
+        msg!("Amp ramp stopped at {}", current_amp);
+        Ok(())
+    }
+
+    /// 현재 시점까지 외삽한 누적 가격을 return data로 반환한다.
+    /// 두 시점의 관측값 차이를 경과 시간으로 나누면 해당 구간의 TWAP (Q64.64)이 된다.
+    pub fn observe(ctx: Context<Observe>) -> Result<Observation> {
+        let pool = &ctx.accounts.amm_info;
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        let now = Clock::get()?.unix_timestamp;
+
+        let mut current = pool.clone().into_inner();
+        current.update_price_cumulative(coin_reserve, pc_reserve, now);
+
+        Ok(Observation {
+            price_coin_cumulative: current.price_coin_cumulative,
+            price_pc_cumulative: current.price_pc_cumulative,
+            timestamp: now,
+        })
+    }
+
+    pub fn set_protocol_fee(
+        ctx: Context<SetProtocolFee>,
+        protocol_fee_numerator: u64,
+    ) -> Result<()> {
+        require!(
+            protocol_fee_numerator <= PROTOCOL_FEE_DENOMINATOR,
+            DexError::InvalidProtocolFee
+        );
+
+        let pool = &mut ctx.accounts.amm_info;
+        pool.protocol_fee_numerator = protocol_fee_numerator;
+
+        msg!(
+            "Protocol fee set to {}/{}",
+            protocol_fee_numerator,
+            PROTOCOL_FEE_DENOMINATOR
+        );
+        Ok(())
+    }
+
+    pub fn set_pool_status(ctx: Context<SetPoolStatus>, status: PoolStatus) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        let old_status = pool.pool_status()?;
+
+        require!(
+            old_status.can_transition_to(status),
+            DexError::InvalidStatusTransition
+        );
+        pool.status = status as u64;
+
+        emit!(StatusChanged {
+            amm_info: pool.key(),
+            old_status,
+            new_status: status,
+        });
+        msg!("Pool status changed: {:?} -> {:?}", old_status, status);
+        Ok(())
+    }
+
+    /// 거래 파라미터 변경 (admin). `swap_base_in`/`swap_base_out`에 적용된다.
+    pub fn update_pool_params(ctx: Context<UpdatePoolParams>, params: PoolParams) -> Result<()> {
+        params.validate()?;
+        let pool = &mut ctx.accounts.amm_info;
+        pool.coin_lot_size = params.coin_lot_size;
+        pool.pc_lot_size = params.pc_lot_size;
+        pool.min_size = params.min_size;
+        pool.min_price_multiplier = params.min_price_multiplier;
+        pool.max_price_multiplier = params.max_price_multiplier;
+        pool.vol_max_cut_ratio = params.vol_max_cut_ratio;
+
+        emit!(PoolParamsUpdated {
+            amm_info: pool.key(),
+            params,
+        });
+        msg!("Pool params updated: {:?}", params);
+        Ok(())
+    }
+
+    /// admin 이전 1단계: 새 admin 후보를 지정한다. `Pubkey::default()`로 진행 중인 제안을 취소.
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        pool.pending_admin = new_admin;
+
+        msg!("Admin transfer proposed: {} -> {}", pool.admin, new_admin);
+        Ok(())
+    }
+
+    /// admin 이전 2단계: 후보가 직접 서명해야 이전이 완료된다.
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        let old_admin = pool.admin;
+        pool.admin = ctx.accounts.pending_admin.key();
+        pool.pending_admin = Pubkey::default();
+
+        msg!("Admin transferred: {} -> {}", old_admin, pool.admin);
+        Ok(())
+    }
+
+    pub fn collect_protocol_fees(ctx: Context<CollectProtocolFees>) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        let coin_fee = pool.protocol_fee_coin;
+        let pc_fee = pool.protocol_fee_pc;
+
+        // PDA signer (amm_authority)
+        let coin_mint_key = ctx.accounts.coin_mint.key();
+        let pc_mint_key = ctx.accounts.pc_mint.key();
+        let seeds: &[&[u8]] = &[
+            b"amm_authority",
+            coin_mint_key.as_ref(),
+            pc_mint_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[seeds];
+
+        // vault → treasury
+        if coin_fee > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.coin_vault.to_account_info(),
+                        mint: ctx.accounts.coin_mint.to_account_info(),
+                        to: ctx.accounts.treasury_coin_account.to_account_info(),
+                        authority: ctx.accounts.amm_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                coin_fee,
+                ctx.accounts.coin_mint.decimals,
+            )?;
+        }
+        if pc_fee > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.pc_vault.to_account_info(),
+                        mint: ctx.accounts.pc_mint.to_account_info(),
+                        to: ctx.accounts.treasury_pc_account.to_account_info(),
+                        authority: ctx.accounts.amm_authority.to_account_info(),
+                    },
+                    signer,
+                ),
+                pc_fee,
+                ctx.accounts.pc_mint.decimals,
+            )?;
+        }
+
+        pool.protocol_fee_coin = 0;
+        pool.protocol_fee_pc = 0;
+
+        msg!("Collected protocol fees: {} coin, {} pc", coin_fee, pc_fee);
+        Ok(())
+    }
+
+    /// 풀 유동성과 프로토콜 수수료로 기록되지 않은 vault 잔고(직접 송금된 토큰 등)를
+    /// recipient 계정으로 보낸다. 누구나 호출할 수 있다 (Uniswap v2 `skim`).
+    pub fn skim(ctx: Context<Skim>) -> Result<()> {
+        let pool = &ctx.accounts.amm_info;
+        let (coin_surplus, pc_surplus) =
+            pool.surplus(ctx.accounts.coin_vault.amount, ctx.accounts.pc_vault.amount)?;
+
+        // PDA signer (amm_authority)
+        let coin_mint_key = ctx.accounts.coin_mint.key();
+        let pc_mint_key = ctx.accounts.pc_mint.key();
+        let seeds: &[&[u8]] = &[
+            b"amm_authority",
+            coin_mint_key.as_ref(),
+            pc_mint_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[seeds];
+
+        // vault → recipient
+        for (vault, mint, recipient, amount) in [
+            (
+                &ctx.accounts.coin_vault,
+                &ctx.accounts.coin_mint,
+                &ctx.accounts.recipient_coin_account,
+                coin_surplus,
+            ),
+            (
+                &ctx.accounts.pc_vault,
+                &ctx.accounts.pc_mint,
+                &ctx.accounts.recipient_pc_account,
+                pc_surplus,
+            ),
+        ] {
+            if amount > 0 {
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        TransferChecked {
+                            from: vault.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: recipient.to_account_info(),
+                            authority: ctx.accounts.amm_authority.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                    mint.decimals,
+                )?;
+            }
+        }
+
+        emit!(Skimmed {
+            amm_info: pool.key(),
+            coin_amount: coin_surplus,
+            pc_amount: pc_surplus,
+        });
+
+        msg!("Skimmed {} coin, {} pc", coin_surplus, pc_surplus);
+        Ok(())
+    }
+
+    /// 풀 유동성을 현재 vault 잔고(프로토콜 수수료 제외)로 맞춘다. 누구나 호출할 수 있다
+    /// (Uniswap v2 `sync`). 직접 송금된 토큰은 기존 LP 몫이 된다.
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        let pool = &mut ctx.accounts.amm_info;
+        let (coin_reserve, pc_reserve) = pool.reserves();
+        pool.update_price_cumulative(coin_reserve, pc_reserve, Clock::get()?.unix_timestamp);
+
+        let (coin_liquidity, pc_liquidity) =
+            pool.vault_liquidity(ctx.accounts.coin_vault.amount, ctx.accounts.pc_vault.amount)?;
+        pool.pool_coin_amount = coin_liquidity;
+        pool.pool_pc_amount = pc_liquidity;
+
+        emit!(ReservesSynced {
+            amm_info: pool.key(),
+            coin_reserve: coin_liquidity,
+            pc_reserve: pc_liquidity,
+        });
+
+        msg!(
+            "Reserves synced: {} coin, {} pc -> {} coin, {} pc",
+            coin_reserve,
+            pc_reserve,
+            coin_liquidity,
+            pc_liquidity
+        );
+        Ok(())
+    }
+
+    /// 이전 버전 풀(`AmmInfo::LEGACY_LEN` 크기, mint 단위 vault `[b"coin_vault", coin_mint]`)을
+    /// 현재 레이아웃으로 옮긴다: 계정을 `AmmInfo::LEN`으로 늘리고, vault 잔고를 풀 단위 vault
+    /// (`[b"coin_vault", amm_info]`)로 옮긴 뒤 기존 vault를 닫는다.
+    /// 이전 풀에는 admin이 없으므로 config admin이 호출하며 그대로 풀 admin이 된다.
+    pub fn migrate_vaults(ctx: Context<MigrateVaults>) -> Result<()> {
+        let info = ctx.accounts.amm_info.to_account_info();
+        {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() == AmmInfo::LEGACY_LEN && data[..8] == *AmmInfo::DISCRIMINATOR,
+                DexError::NotLegacyPool
+            );
+        }
+
+        // 늘어나는 공간만큼 rent를 admin이 채운 뒤 확장 (새 필드는 0으로 채워진다)
+        let top_up = Rent::get()?
+            .minimum_balance(AmmInfo::LEN)
+            .saturating_sub(info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        info.resize(AmmInfo::LEN)?;
+        let mut pool = AmmInfo::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require_keys_eq!(
+            pool.coin_vault,
+            ctx.accounts.legacy_coin_vault.key(),
+            DexError::InvalidVault
+        );
+        require_keys_eq!(
+            pool.pc_vault,
+            ctx.accounts.legacy_pc_vault.key(),
+            DexError::InvalidVault
+        );
+
+        let coin_amount = ctx.accounts.legacy_coin_vault.amount;
+        let pc_amount = ctx.accounts.legacy_pc_vault.amount;
+
+        // PDA signer (amm_authority)
+        let coin_mint_key = ctx.accounts.coin_mint.key();
+        let pc_mint_key = ctx.accounts.pc_mint.key();
+        let seeds: &[&[u8]] = &[
+            b"amm_authority",
+            coin_mint_key.as_ref(),
+            pc_mint_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[seeds];
+
+        // 기존 vault → 새 vault (잔고 전부), 이후 기존 vault 닫기 (rent는 admin에게)
+        for (legacy_vault, mint, new_vault, amount) in [
+            (
+                &ctx.accounts.legacy_coin_vault,
+                &ctx.accounts.coin_mint,
+                &ctx.accounts.coin_vault,
+                coin_amount,
+            ),
+            (
+                &ctx.accounts.legacy_pc_vault,
+                &ctx.accounts.pc_mint,
+                &ctx.accounts.pc_vault,
+                pc_amount,
+            ),
+        ] {
+            if amount > 0 {
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        TransferChecked {
+                            from: legacy_vault.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: new_vault.to_account_info(),
+                            authority: ctx.accounts.amm_authority.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                    mint.decimals,
+                )?;
+            }
+            token_interface::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                CloseAccount {
+                    account: legacy_vault.to_account_info(),
+                    destination: ctx.accounts.admin.to_account_info(),
+                    authority: ctx.accounts.amm_authority.to_account_info(),
+                },
+                signer,
+            ))?;
+        }
+
+        // 이전 버전에는 status를 바꾸는 instruction이 없었다 — 새 풀처럼 DepositOnly에서 시작
+        if pool.pool_status()? == PoolStatus::Uninitialized {
+            pool.status = PoolStatus::DepositOnly as u64;
+        }
+
+        pool.coin_vault = ctx.accounts.coin_vault.key();
+        pool.pc_vault = ctx.accounts.pc_vault.key();
+        pool.amm_coin_account = ctx.accounts.coin_vault.key();
+        pool.amm_pc_account = ctx.accounts.pc_vault.key();
+
+        // 이전 버전은 스왑 후 유동성을 기록하지 않았으므로 옮긴 vault 잔고로 다시 맞춘다
+        pool.pool_coin_amount = coin_amount;
+        pool.pool_pc_amount = pc_amount;
+        pool.pool_lp_amount = ctx.accounts.lp_mint.supply;
+
+        // 새 필드 — 수수료는 이전 고정값 그대로, 프로토콜 수수료 적립분/누적 가격/amp는 0에서 시작
+        let config = &mut ctx.accounts.amm_config;
+        pool.trade_fee_numerator = LEGACY_TRADE_FEE_BPS;
+        pool.trade_fee_denominator = FEE_DENOMINATOR;
+        pool.admin = config.admin;
+        pool.pending_admin = Pubkey::default();
+        pool.protocol_fee_numerator = config.default_protocol_fee_numerator;
+        let now = Clock::get()?.unix_timestamp;
+        pool.last_update_timestamp = now;
+        pool.curve_type = CurveType::ConstantProduct as u64;
+        pool.ramp_start_timestamp = now;
+        pool.ramp_stop_timestamp = now;
+        pool.token_program = ctx.accounts.token_program.key();
+
+        config.register(
+            &mut ctx.accounts.pool_registry,
+            PoolEntry {
+                amm_info: info.key(),
+                coin_mint: pool.coin_mint,
+                pc_mint: pool.pc_mint,
+                lp_mint: pool.lp_mint,
+            },
+        )?;
+        pool.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Vaults migrated: {} coin, {} pc moved to {} / {}",
+            coin_amount,
+            pc_amount,
+            pool.coin_vault,
+            pool.pc_vault
+        );
+        Ok(())
+    }
+}
+
+// =========== Helper, Accounts, Errors (same as before, 단 LEN 수정) ===========
+
+pub fn calculate_amount_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<u64> {
+    require!(amount_in > 0, DexError::InvalidAmount);
+    require!(reserve_in > 0 && reserve_out > 0, DexError::InsufficientLiquidity);
+    require!(fee_numerator < fee_denominator, DexError::InvalidFeeTier);
+
+    let amount_in_with_fee = U256::from(amount_in) * U256::from(fee_denominator - fee_numerator);
+    let numerator = amount_in_with_fee * U256::from(reserve_out);
+    let denominator = U256::from(reserve_in) * U256::from(fee_denominator) + amount_in_with_fee;
+
+    math::div_u256(numerator, denominator, Rounding::Down)
+}
+
+/// `calculate_amount_out`의 역함수: 정확히 `amount_out`을 받기 위해 필요한 최소 입력량.
+/// 나눗셈은 올림 처리하여 반올림 오차가 항상 풀에 유리하도록 한다.
+pub fn calculate_amount_in(
+    amount_out: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<u64> {
+    require!(amount_out > 0, DexError::InvalidAmount);
+    require!(reserve_in > 0 && reserve_out > 0, DexError::InsufficientLiquidity);
+    require!(amount_out < reserve_out, DexError::InsufficientLiquidity);
+    require!(fee_numerator < fee_denominator, DexError::InvalidFeeTier);
+
+    let numerator = U256::from(reserve_in) * U256::from(amount_out) * U256::from(fee_denominator);
+    let denominator =
+        U256::from(reserve_out - amount_out) * U256::from(fee_denominator - fee_numerator);
+
+    math::div_u256(numerator, denominator, Rounding::Up)
+}
+
+/// flash swap으로 빌린 `amount`를 같은 토큰으로 갚을 때 원금에 더해야 하는 최소 수수료.
+/// (amount + fee) * (den - num) >= amount * den 을 만족하는 최소값 (올림).
+pub fn calculate_flash_fee(amount: u64, fee_numerator: u64, fee_denominator: u64) -> Result<u64> {
+    require!(fee_numerator < fee_denominator, DexError::InvalidFeeTier);
+    math::mul_div(
+        amount,
+        fee_numerator,
+        fee_denominator - fee_numerator,
+        Rounding::Up,
+    )
+}
+
+/// flash swap의 순 흐름 (`borrowed`, `repaid`는 (coin, pc)). 한쪽은 순입력, 반대쪽은 순출력이면
+/// 그에 해당하는 (방향, 입력, 출력) 스왑이고, 그 밖의 경우(같은 토큰으로 상환 등)는 `None`.
+pub fn flash_net_swap(
+    borrowed: (u64, u64),
+    repaid: (u64, u64),
+) -> Option<(SwapDirection, u64, u64)> {
+    let (coin_out, pc_out) = borrowed;
+    let (coin_in, pc_in) = repaid;
+    if coin_in > coin_out && pc_out > pc_in {
+        Some((SwapDirection::CoinToPc, coin_in - coin_out, pc_out - pc_in))
+    } else if pc_in > pc_out && coin_out > coin_in {
+        Some((SwapDirection::PcToCoin, pc_in - pc_out, coin_out - coin_in))
+    } else {
+        None
+    }
+}
+
+/// 트랜잭션 deadline(unix timestamp)이 지났으면 실패. `None`이면 검사하지 않는다.
+pub fn check_deadline(deadline: Option<i64>, clock: &Clock) -> Result<()> {
+    if let Some(deadline) = deadline {
+        require!(clock.unix_timestamp <= deadline, DexError::DeadlineExceeded);
+    }
+    Ok(())
+}
+
+#[account]
+pub struct AmmInfo {
+    pub status: u64,
+    // pub nonce: u64, // 제거
+    pub order_num: u64,
+    pub depth: u64,
+
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+
+    pub lp_mint: Pubkey,
+
+    pub open_time: u64,
+
+    pub punish_coin_amount: u64,
+    pub punish_pc_amount: u64,
+
+    pub pool_coin_amount: u64,
+    pub pool_pc_amount: u64,
+    pub pool_lp_amount: u64,
+
+    pub min_size: u64,
+    pub vol_max_cut_ratio: u64,
+    pub amount_wave_ratio: u64,
+    pub coin_lot_size: u64,
+    pub pc_lot_size: u64,
+
+    pub min_price_multiplier: u64,
+    pub max_price_multiplier: u64,
+
+    pub sys_decimal_value: u64,
 
     pub amm_coin_account: Pubkey,
     pub amm_pc_account: Pubkey,
+
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    // 스왑 수수료 중 프로토콜 몫 (PROTOCOL_FEE_DENOMINATOR 기준)
+    pub protocol_fee_numerator: u64,
+    // 적립되었지만 아직 수거되지 않은 프로토콜 수수료 — vault 잔고에 포함되어 있지만 유동성은 아님
+    pub protocol_fee_coin: u64,
+    pub protocol_fee_pc: u64,
+
+    // Uniswap v2 방식 누적 가격 (Q64.64 * 초). coin 가격 = pc/coin, pc 가격 = coin/pc
+    pub price_coin_cumulative: u128,
+    pub price_pc_cumulative: u128,
+    pub last_update_timestamp: i64,
+
+    // 스왑 곡선 (CurveType as u64). StableSwap의 amp는 ramp 구간 동안 선형 보간
+    pub curve_type: u64,
+    pub initial_amp: u64,
+    pub target_amp: u64,
+    pub ramp_start_timestamp: i64,
+    pub ramp_stop_timestamp: i64,
+
+    // vault/mint를 소유한 토큰 프로그램 (SPL Token 또는 Token-2022) — router가 hop마다 검증
+    pub token_program: Pubkey,
+}
+
+impl AmmInfo {
+    // 계산: discriminator(8) + u64_count*8 + pubkey_count*32
+    // u64_count = 25 (status, order_num, depth, open_time, punish_* 2, pool_* 3, min_size, vol_max_cut_ratio, amount_wave_ratio,
+    // coin_lot_size, pc_lot_size, min_price_multiplier, max_price_multiplier, sys_decimal_value, trade_fee_* 2,
+    // protocol_fee_numerator, protocol_fee_coin, protocol_fee_pc, curve_type, initial_amp, target_amp) => 25
+    // pubkey_count = 10 (coin_mint, pc_mint, coin_vault, pc_vault, lp_mint, amm_coin_account, amm_pc_account,
+    // admin, pending_admin, token_program) => 10
+    // u128_count = 2 (price_coin_cumulative, price_pc_cumulative)
+    // i64_count = 3 (last_update_timestamp, ramp_start_timestamp, ramp_stop_timestamp)
+    pub const LEN: usize = 8 + 25 * 8 + 10 * 32 + 2 * 16 + 3 * 8; // = 8 + 200 + 320 + 32 + 24 = 584
+
+    // 이전 버전 (풀 단위 vault 이전) 계정 크기 — 현재 레이아웃의 amm_pc_account까지와 같다
+    pub const LEGACY_LEN: usize = 8 + 17 * 8 + 7 * 32; // = 368
+
+    /// 가격 계산에 쓰는 풀 유동성 (coin, pc). vault 잔고가 아니라 풀이 직접 기록한 값이므로
+    /// vault로 직접 보낸 토큰은 `sync` 전까지 가격에 반영되지 않는다.
+    pub fn reserves(&self) -> (u64, u64) {
+        (self.pool_coin_amount, self.pool_pc_amount)
+    }
+
+    /// vault 잔고에서 수거 전 프로토콜 수수료를 뺀 양 (coin, pc)
+    pub fn vault_liquidity(
+        &self,
+        coin_vault_amount: u64,
+        pc_vault_amount: u64,
+    ) -> Result<(u64, u64)> {
+        let coin_reserve = coin_vault_amount
+            .checked_sub(self.protocol_fee_coin)
+            .ok_or(DexError::MathOverflow)?;
+        let pc_reserve = pc_vault_amount
+            .checked_sub(self.protocol_fee_pc)
+            .ok_or(DexError::MathOverflow)?;
+        Ok((coin_reserve, pc_reserve))
+    }
+
+    /// 풀 유동성으로 기록되지 않은 vault 잔고 (coin, pc) — `skim` 대상
+    pub fn surplus(&self, coin_vault_amount: u64, pc_vault_amount: u64) -> Result<(u64, u64)> {
+        let (coin_liquidity, pc_liquidity) =
+            self.vault_liquidity(coin_vault_amount, pc_vault_amount)?;
+        let coin_surplus = coin_liquidity
+            .checked_sub(self.pool_coin_amount)
+            .ok_or(DexError::VaultBelowReserves)?;
+        let pc_surplus = pc_liquidity
+            .checked_sub(self.pool_pc_amount)
+            .ok_or(DexError::VaultBelowReserves)?;
+        Ok((coin_surplus, pc_surplus))
+    }
+
+    /// 공급 반영: 유동성과 LP 공급량을 늘린다
+    pub fn add_liquidity(
+        &mut self,
+        coin_amount: u64,
+        pc_amount: u64,
+        lp_amount: u64,
+    ) -> Result<()> {
+        self.pool_coin_amount = self
+            .pool_coin_amount
+            .checked_add(coin_amount)
+            .ok_or(DexError::MathOverflow)?;
+        self.pool_pc_amount = self
+            .pool_pc_amount
+            .checked_add(pc_amount)
+            .ok_or(DexError::MathOverflow)?;
+        self.pool_lp_amount = self
+            .pool_lp_amount
+            .checked_add(lp_amount)
+            .ok_or(DexError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// 출금 반영: 유동성과 LP 공급량을 줄인다
+    pub fn remove_liquidity(
+        &mut self,
+        coin_amount: u64,
+        pc_amount: u64,
+        lp_amount: u64,
+    ) -> Result<()> {
+        self.pool_coin_amount = self
+            .pool_coin_amount
+            .checked_sub(coin_amount)
+            .ok_or(DexError::MathOverflow)?;
+        self.pool_pc_amount = self
+            .pool_pc_amount
+            .checked_sub(pc_amount)
+            .ok_or(DexError::MathOverflow)?;
+        self.pool_lp_amount = self
+            .pool_lp_amount
+            .checked_sub(lp_amount)
+            .ok_or(DexError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// 변경 전 유동성으로 마지막 갱신 이후 경과 시간만큼 누적 가격을 더한다.
+    /// 누적값은 wrapping 연산 — 소비자는 두 관측값의 wrapping 차분을 사용해야 한다.
+    pub fn update_price_cumulative(&mut self, coin_reserve: u64, pc_reserve: u64, now: i64) {
+        let elapsed = now.saturating_sub(self.last_update_timestamp);
+        if elapsed > 0 && coin_reserve > 0 && pc_reserve > 0 {
+            let price_coin = ((pc_reserve as u128) << 64) / coin_reserve as u128;
+            let price_pc = ((coin_reserve as u128) << 64) / pc_reserve as u128;
+            self.price_coin_cumulative = self
+                .price_coin_cumulative
+                .wrapping_add(price_coin.wrapping_mul(elapsed as u128));
+            self.price_pc_cumulative = self
+                .price_pc_cumulative
+                .wrapping_add(price_pc.wrapping_mul(elapsed as u128));
+        }
+        if now > self.last_update_timestamp {
+            self.last_update_timestamp = now;
+        }
+    }
+
+    pub fn pool_status(&self) -> Result<PoolStatus> {
+        PoolStatus::from_u64(self.status)
+    }
+
+    pub fn curve_type(&self) -> Result<CurveType> {
+        CurveType::from_u64(self.curve_type)
+    }
+
+    /// `now` 시점의 StableSwap amplification 계수 (constant product 풀은 0)
+    pub fn amp(&self, now: i64) -> u64 {
+        curve::current_amp(
+            self.initial_amp,
+            self.target_amp,
+            self.ramp_start_timestamp,
+            self.ramp_stop_timestamp,
+            now,
+        )
+    }
+
+    /// 풀의 곡선과 수수료로 계산한 스왑 출력량
+    pub fn swap_amount_out(
+        &self,
+        amount_in: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        now: i64,
+    ) -> Result<u64> {
+        curve::swap_amount_out(
+            self.curve_type()?,
+            self.amp(now),
+            amount_in,
+            reserve_in,
+            reserve_out,
+            self.trade_fee_numerator,
+            self.trade_fee_denominator,
+        )
+    }
+
+    /// 풀의 곡선과 수수료로 계산한 정확한 출력에 필요한 입력량
+    pub fn swap_amount_in(
+        &self,
+        amount_out: u64,
+        reserve_in: u64,
+        reserve_out: u64,
+        now: i64,
+    ) -> Result<u64> {
+        curve::swap_amount_in(
+            self.curve_type()?,
+            self.amp(now),
+            amount_out,
+            reserve_in,
+            reserve_out,
+            self.trade_fee_numerator,
+            self.trade_fee_denominator,
+        )
+    }
+
+    /// 스왑 입력량에 대한 수수료 중 프로토콜 몫
+    pub fn protocol_fee(&self, amount_in: u64) -> u64 {
+        let trade_fee = amount_in as u128 * self.trade_fee_numerator as u128
+            / self.trade_fee_denominator as u128;
+        (trade_fee * self.protocol_fee_numerator as u128 / PROTOCOL_FEE_DENOMINATOR as u128) as u64
+    }
+
+    /// (source, destination) 쪽 lot 크기
+    pub fn lot_sizes(&self, direction: SwapDirection) -> (u64, u64) {
+        direction.orient(self.coin_lot_size.max(1), self.pc_lot_size.max(1))
+    }
+
+    /// `now` 시점 곡선 기준 한계 가격 pc/coin (분자, 분모). `curve::spot_price` 참고
+    pub fn spot_price(&self, coin_reserve: u64, pc_reserve: u64, now: i64) -> Result<(U256, U256)> {
+        curve::spot_price(self.curve_type()?, self.amp(now), coin_reserve, pc_reserve)
+    }
+
+    /// 체결 검사: coin 쪽 수량이 `min_size` 이상이고, 체결 가격(pc/coin)이 스왑 전 풀 가격의
+    /// `[min_price_multiplier, max_price_multiplier] / PRICE_MULTIPLIER_DENOMINATOR` 안에 있는지.
+    /// 풀 가격은 곡선의 한계 가격(`spot_price`)이며, multiplier가 0이면 그쪽 한도는 없다.
+    pub fn check_trade(
+        &self,
+        direction: SwapDirection,
+        amount_in: u64,
+        amount_out: u64,
+        coin_reserve: u64,
+        pc_reserve: u64,
+        now: i64,
+    ) -> Result<()> {
+        // (source, destination) → (coin, pc)
+        let (coin_amount, pc_amount) = direction.orient(amount_in, amount_out);
+        require!(coin_amount >= self.min_size, DexError::BelowMinSize);
+        if self.min_price_multiplier == 0 && self.max_price_multiplier == 0 {
+            return Ok(());
+        }
+
+        // pc_amount / coin_amount 와 price_pc / price_coin 비교
+        let (price_pc, price_coin) = self.spot_price(coin_reserve, pc_reserve, now)?;
+        let executed =
+            U256::from(pc_amount) * price_coin * U256::from(PRICE_MULTIPLIER_DENOMINATOR);
+        let spot = price_pc * U256::from(coin_amount);
+        require!(
+            self.min_price_multiplier == 0
+                || executed >= spot * U256::from(self.min_price_multiplier),
+            DexError::PriceOutOfBand
+        );
+        require!(
+            self.max_price_multiplier == 0
+                || executed <= spot * U256::from(self.max_price_multiplier),
+            DexError::PriceOutOfBand
+        );
+        Ok(())
+    }
+
+    /// 스왑 후 풀 가격이 스왑 전 (`coin_before`, `pc_before`) 가격에서
+    /// `vol_max_cut_ratio / PRICE_MULTIPLIER_DENOMINATOR`보다 많이 움직였으면 거부 (0이면 한도 없음).
+    /// 가격은 `check_trade`와 같이 곡선의 한계 가격이다.
+    pub fn check_price_impact(&self, coin_before: u64, pc_before: u64, now: i64) -> Result<()> {
+        if self.vol_max_cut_ratio == 0 {
+            return Ok(());
+        }
+        let (coin_after, pc_after) = self.reserves();
+        let (pc_before, coin_before) = self.spot_price(coin_before, pc_before, now)?;
+        let (pc_after, coin_after) = self.spot_price(coin_after, pc_after, now)?;
+        // |after / before - 1| = |pc_after * coin_before - pc_before * coin_after| / (pc_before * coin_after)
+        let after = pc_after * coin_before;
+        let before = pc_before * coin_after;
+        let moved = if after > before {
+            after - before
+        } else {
+            before - after
+        };
+        require!(
+            moved * U256::from(PRICE_MULTIPLIER_DENOMINATOR)
+                <= before * U256::from(self.vol_max_cut_ratio),
+            DexError::PriceImpactTooHigh
+        );
+        Ok(())
+    }
+
+    /// 스왑 결과 반영: 입력 쪽에 프로토콜 수수료를 적립하고 나머지로 풀 유동성을 갱신
+    pub fn apply_swap(
+        &mut self,
+        direction: SwapDirection,
+        amount_in: u64,
+        amount_out: u64,
+    ) -> Result<()> {
+        let protocol_fee = self.protocol_fee(amount_in);
+        let liquidity_in = amount_in - protocol_fee;
+
+        match direction {
+            SwapDirection::CoinToPc => {
+                self.protocol_fee_coin = self
+                    .protocol_fee_coin
+                    .checked_add(protocol_fee)
+                    .ok_or(DexError::MathOverflow)?;
+                self.pool_coin_amount = self
+                    .pool_coin_amount
+                    .checked_add(liquidity_in)
+                    .ok_or(DexError::MathOverflow)?;
+                self.pool_pc_amount = self
+                    .pool_pc_amount
+                    .checked_sub(amount_out)
+                    .ok_or(DexError::InsufficientLiquidity)?;
+            }
+            SwapDirection::PcToCoin => {
+                self.protocol_fee_pc = self
+                    .protocol_fee_pc
+                    .checked_add(protocol_fee)
+                    .ok_or(DexError::MathOverflow)?;
+                self.pool_pc_amount = self
+                    .pool_pc_amount
+                    .checked_add(liquidity_in)
+                    .ok_or(DexError::MathOverflow)?;
+                self.pool_coin_amount = self
+                    .pool_coin_amount
+                    .checked_sub(amount_out)
+                    .ok_or(DexError::InsufficientLiquidity)?;
+            }
+        }
+        Ok(())
+    }
 }
 
-impl AmmInfo {
-    // 계산: discriminator(8) + u64_count*8 + pubkey_count*32
-    // u64_count = 17 (status, order_num, depth, open_time, punish_* 2, pool_* 3, min_size, vol_max_cut_ratio, amount_wave_ratio,
-    // coin_lot_size, pc_lot_size, min_price_multiplier, max_price_multiplier, sys_decimal_value) => 17
-    // pubkey_count = 7 (coin_mint, pc_mint, coin_vault, pc_vault, lp_mint, amm_coin_account, amm_pc_account) => 7
-    pub const LEN: usize = 8 + 17 * 8 + 7 * 32; // = 8 + 136 + 224 = 368
+/// 풀 상태 머신. `AmmInfo.status`에는 variant 순서대로 0..=4 의 u64로 저장된다.
+///
+/// | 상태           | deposit | swap | withdraw |
+/// |----------------|---------|------|----------|
+/// | Uninitialized  |    x    |  x   |    x     |
+/// | DepositOnly    |    o    |  x   |    o     |
+/// | WithdrawOnly   |    x    |  x   |    o     |
+/// | Trading        |    o    |  o   |    o     |
+/// | Disabled       |    x    |  x   |    x     |
+///
+/// `initialize_pool` 이후 풀은 DepositOnly로 시작한다. Disabled는 종료 상태이며
+/// Uninitialized로는 되돌릴 수 없다.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolStatus {
+    Uninitialized,
+    DepositOnly,
+    WithdrawOnly,
+    Trading,
+    Disabled,
+}
+
+/// `update_pool_params`로 바꾸는 거래 파라미터.
+///
+/// - lot 크기: 정확한 입력은 입력 쪽 lot으로 내림, 정확한 출력은 출력 쪽 lot으로 올림
+/// - `min_size`: 스왑의 coin 쪽 최소 수량
+/// - `min/max_price_multiplier`: 스왑 전 가격 대비 체결 가격 밴드 (0이면 한도 없음)
+/// - `vol_max_cut_ratio`: 스왑 한 번의 최대 가격 변동 (0이면 한도 없음)
+///
+/// 비율은 모두 `PRICE_MULTIPLIER_DENOMINATOR` 기준이다.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolParams {
+    pub coin_lot_size: u64,
+    pub pc_lot_size: u64,
+    pub min_size: u64,
+    pub min_price_multiplier: u64,
+    pub max_price_multiplier: u64,
+    pub vol_max_cut_ratio: u64,
+}
+
+impl PoolParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.coin_lot_size > 0 && self.pc_lot_size > 0,
+            DexError::InvalidPoolParams
+        );
+        // 밴드는 스왑 전 가격(1.0)을 포함해야 한다
+        require!(
+            self.min_price_multiplier <= PRICE_MULTIPLIER_DENOMINATOR
+                && (self.max_price_multiplier == 0
+                    || self.max_price_multiplier >= PRICE_MULTIPLIER_DENOMINATOR),
+            DexError::InvalidPoolParams
+        );
+        require!(
+            self.vol_max_cut_ratio <= PRICE_MULTIPLIER_DENOMINATOR,
+            DexError::InvalidPoolParams
+        );
+        Ok(())
+    }
+}
+
+impl PoolStatus {
+    pub fn from_u64(status: u64) -> Result<Self> {
+        match status {
+            0 => Ok(PoolStatus::Uninitialized),
+            1 => Ok(PoolStatus::DepositOnly),
+            2 => Ok(PoolStatus::WithdrawOnly),
+            3 => Ok(PoolStatus::Trading),
+            4 => Ok(PoolStatus::Disabled),
+            _ => err!(DexError::InvalidStatus),
+        }
+    }
+
+    pub fn can_deposit(self) -> bool {
+        matches!(self, PoolStatus::DepositOnly | PoolStatus::Trading)
+    }
+
+    pub fn can_swap(self) -> bool {
+        self == PoolStatus::Trading
+    }
+
+    pub fn can_withdraw(self) -> bool {
+        matches!(
+            self,
+            PoolStatus::DepositOnly | PoolStatus::WithdrawOnly | PoolStatus::Trading
+        )
+    }
+
+    pub fn can_transition_to(self, next: PoolStatus) -> bool {
+        self != next
+            && self != PoolStatus::Disabled
+            && self != PoolStatus::Uninitialized
+            && next != PoolStatus::Uninitialized
+    }
+}
+
+/// 스왑 방향. vault는 항상 AmmInfo에 기록된 coin_vault/pc_vault 중에서 이 값으로 결정된다.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapDirection {
+    CoinToPc,
+    PcToCoin,
+}
+
+impl SwapDirection {
+    /// (coin, pc) 쌍을 (source, destination) 순서로 정렬
+    pub fn orient<T>(self, coin: T, pc: T) -> (T, T) {
+        match self {
+            SwapDirection::CoinToPc => (coin, pc),
+            SwapDirection::PcToCoin => (pc, coin),
+        }
+    }
+}
+
+/// 한쪽 토큰만 받거나 넣을 때 대상 토큰
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolSide {
+    Coin,
+    Pc,
+}
+
+impl PoolSide {
+    /// 반대쪽 토큰을 이 쪽으로 바꾸는 스왑 방향
+    pub fn swap_direction_into(self) -> SwapDirection {
+        match self {
+            PoolSide::Coin => SwapDirection::PcToCoin,
+            PoolSide::Pc => SwapDirection::CoinToPc,
+        }
+    }
+
+    /// 이 쪽 토큰을 반대쪽으로 바꾸는 스왑 방향
+    pub fn swap_direction_from(self) -> SwapDirection {
+        match self {
+            PoolSide::Coin => SwapDirection::CoinToPc,
+            PoolSide::Pc => SwapDirection::PcToCoin,
+        }
+    }
+}
+
+// Context structs (InitializePool, Deposit, SwapBaseIn, Withdraw) - 동일하게 유지
+// Errors - 동일하게 유지
+
+#[derive(Accounts)]
+pub struct InitializeAmmConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = AmmConfig::LEN,
+        seeds = [b"amm_config"],
+        bump
+    )]
+    pub amm_config: Account<'info, AmmConfig>,
+
+    // 누구나 먼저 호출해 config admin을 차지하지 못하도록 업그레이드 권한자로 제한
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ DexError::Unauthorized)]
+    pub program: Program<'info, crate::program::SolanaDex>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ DexError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAmmConfig<'info> {
+    #[account(mut, seeds = [b"amm_config"], bump, has_one = admin @ DexError::Unauthorized)]
+    pub amm_config: Account<'info, AmmConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = AmmInfo::LEN,
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump
+    )]
+    pub amm_info: Account<'info, AmmInfo>,
+    /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
+    #[account(
+        seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump
+    )]
+    pub amm_authority: AccountInfo<'info>,
+
+    // coin/pc/LP mint는 모두 같은 token program (SPL Token 또는 Token-2022)을 쓴다
+    #[account(mint::token_program = token_program)]
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program)]
+    pub pc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = coin_mint,
+        token::authority = amm_authority,
+        seeds = [b"coin_vault", amm_info.key().as_ref()],
+        bump
+    )]
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = pc_mint,
+        token::authority = amm_authority,
+        seeds = [b"pc_vault", amm_info.key().as_ref()],
+        bump
+    )]
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 6,
+        mint::authority = amm_authority,
+        seeds = [b"lp_mint", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    // MINIMUM_LIQUIDITY를 보관하는 계정 — amm_authority 소유이며 인출 경로가 없다
+    #[account(
+        init,
+        payer = payer,
+        token::mint = lp_mint,
+        token::authority = amm_authority,
+        seeds = [b"locked_lp", lp_mint.key().as_ref()],
+        bump
+    )]
+    pub locked_lp_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"amm_config"], bump)]
+    pub amm_config: Account<'info, AmmConfig>,
+    // 이 풀이 들어갈 레지스트리 페이지 — 페이지의 첫 풀을 만드는 payer가 생성 비용을 낸다
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PoolRegistry::LEN,
+        seeds = [b"pool_registry".as_ref(), &amm_config.registry_page().to_le_bytes()],
+        bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: Receives the creation fee; address is checked against amm_config.
+    #[account(mut, address = amm_config.fee_receiver @ DexError::InvalidFeeReceiver)]
+    pub fee_receiver: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump,
+        has_one = coin_mint @ DexError::InvalidCoinMint,
+        has_one = pc_mint @ DexError::InvalidPcMint,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault,
+        has_one = lp_mint @ DexError::InvalidLpMint
+    )]
+    pub amm_info: Account<'info, AmmInfo>,
+    /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
+    #[account(seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
+    pub amm_authority: AccountInfo<'info>,
+
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    pub pc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, seeds = [b"locked_lp", lp_mint.key().as_ref()], bump)]
+    pub locked_lp_vault: InterfaceAccount<'info, TokenAccount>,
+
+    // 사용자가 지불하는 계정은 mint + owner, 받는 계정은 mint만 검사
+    #[account(
+        mut,
+        constraint = user_coin_account.mint == coin_mint.key() @ DexError::InvalidUserTokenMint,
+        constraint = user_coin_account.owner == user_authority.key() @ DexError::InvalidUserTokenOwner
+    )]
+    pub user_coin_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_pc_account.mint == pc_mint.key() @ DexError::InvalidUserTokenMint,
+        constraint = user_pc_account.owner == user_authority.key() @ DexError::InvalidUserTokenOwner
+    )]
+    pub user_pc_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_lp_account.mint == lp_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub user_lp_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub user_authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSingleSide<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump,
+        has_one = coin_mint @ DexError::InvalidCoinMint,
+        has_one = pc_mint @ DexError::InvalidPcMint,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault,
+        has_one = lp_mint @ DexError::InvalidLpMint
+    )]
+    pub amm_info: Account<'info, AmmInfo>,
+    /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
+    #[account(seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
+    pub amm_authority: AccountInfo<'info>,
+
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    pub pc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    // 지불 계정의 mint는 side가 정해진 뒤 instruction에서 검사
+    #[account(
+        mut,
+        constraint = user_source_account.owner == user_authority.key() @ DexError::InvalidUserTokenOwner
+    )]
+    pub user_source_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_lp_account.mint == lp_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub user_lp_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub user_authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-// Context structs (InitializePool, Deposit, SwapBaseIn, Withdraw) - 동일하게 유지
-// Errors - 동일하게 유지
-
 #[derive(Accounts)]
-pub struct InitializePool<'info> {
+pub struct SwapBaseIn<'info> {
     #[account(
-        init,
-        payer = payer,
-        space = AmmInfo::LEN,
+        mut,
         seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
-        bump
+        bump,
+        has_one = coin_mint @ DexError::InvalidCoinMint,
+        has_one = pc_mint @ DexError::InvalidPcMint,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault
     )]
     pub amm_info: Account<'info, AmmInfo>,
     /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
-    #[account(
-        seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
-        bump
-    )]
+    #[account(seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
     pub amm_authority: AccountInfo<'info>,
 
-    pub coin_mint: Account<'info, Mint>,
-    pub pc_mint: Account<'info, Mint>,
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    pub pc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
 
+    // 지불 계정의 owner는 방향이 정해진 뒤 instruction에서 검사
     #[account(
-        init,
-        payer = payer,
-        token::mint = coin_mint,
-        token::authority = amm_authority,
-        seeds = [b"coin_vault", coin_mint.key().as_ref()],
-        bump
+        mut,
+        constraint = user_coin_account.mint == coin_mint.key() @ DexError::InvalidUserTokenMint
     )]
-    pub coin_vault: Account<'info, TokenAccount>,
-
+    pub user_coin_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
-        init,
-        payer = payer,
-        token::mint = pc_mint,
-        token::authority = amm_authority,
-        seeds = [b"pc_vault", pc_mint.key().as_ref()],
-        bump
+        mut,
+        constraint = user_pc_account.mint == pc_mint.key() @ DexError::InvalidUserTokenMint
     )]
-    pub pc_vault: Account<'info, TokenAccount>,
+    pub user_pc_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub user_authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
+#[derive(Accounts)]
+pub struct SwapBaseOut<'info> {
     #[account(
-        init,
-        payer = payer,
-        mint::decimals = 6,
-        mint::authority = amm_authority,
-        seeds = [b"lp_mint", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump,
+        has_one = coin_mint @ DexError::InvalidCoinMint,
+        has_one = pc_mint @ DexError::InvalidPcMint,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault
     )]
-    pub lp_mint: Account<'info, Mint>,
+    pub amm_info: Account<'info, AmmInfo>,
+    /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
+    #[account(seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
+    pub amm_authority: AccountInfo<'info>,
+
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    pub pc_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
+    // 지불 계정의 owner는 방향이 정해진 뒤 instruction에서 검사
+    #[account(
+        mut,
+        constraint = user_coin_account.mint == coin_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub user_coin_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_pc_account.mint == pc_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub user_pc_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub user_authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct Deposit<'info> {
-    #[account(mut, seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
+pub struct FlashSwap<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump,
+        has_one = coin_mint @ DexError::InvalidCoinMint,
+        has_one = pc_mint @ DexError::InvalidPcMint,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault,
+        has_one = lp_mint @ DexError::InvalidLpMint
+    )]
     pub amm_info: Account<'info, AmmInfo>,
     /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
     #[account(seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
     pub amm_authority: AccountInfo<'info>,
 
-    pub coin_mint: Account<'info, Mint>,
-    pub pc_mint: Account<'info, Mint>,
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    pub pc_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
-    pub coin_vault: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub pc_vault: Account<'info, TokenAccount>,
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
-    pub lp_mint: Account<'info, Mint>,
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
 
+    // 콜백 전후로 비교만 한다 (쓰기 없음)
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(seeds = [b"locked_lp", lp_mint.key().as_ref()], bump)]
+    pub locked_lp_vault: InterfaceAccount<'info, TokenAccount>,
+
+    // 빌린 토큰을 받을 계정 (보통 callback_program 소유)
+    #[account(
+        mut,
+        constraint = receiver_coin_account.mint == coin_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub receiver_coin_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = receiver_pc_account.mint == pc_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub receiver_pc_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Arbitrary receiver program; only invoked, and repayment is checked afterwards.
+    #[account(executable)]
+    pub callback_program: AccountInfo<'info>,
+
+    pub initiator: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// flash swap 콜백 인자. 수신 프로그램의 `flash_swap_callback(initiator, coin_amount,
+/// pc_amount, coin_fee, pc_fee, data)`와 borsh 레이아웃이 같다.
+/// `*_fee`는 같은 토큰으로 갚을 때 원금에 더해야 하는 최소 수수료.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FlashSwapCallbackArgs {
+    pub initiator: Pubkey,
+    pub coin_amount: u64,
+    pub pc_amount: u64,
+    pub coin_fee: u64,
+    pub pc_fee: u64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump,
+        has_one = coin_mint @ DexError::InvalidCoinMint,
+        has_one = pc_mint @ DexError::InvalidPcMint,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault,
+        has_one = lp_mint @ DexError::InvalidLpMint
+    )]
+    pub amm_info: Account<'info, AmmInfo>,
+
+    /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
+    #[account(
+        seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump
+    )]
+    pub amm_authority: AccountInfo<'info>,
+
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    pub pc_mint: InterfaceAccount<'info, Mint>,
     #[account(mut)]
-    pub user_coin_account: Account<'info, TokenAccount>,
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
     #[account(mut)]
-    pub user_pc_account: Account<'info, TokenAccount>,
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
-    pub user_lp_account: Account<'info, TokenAccount>,
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_coin_account.mint == coin_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub user_coin_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_pc_account.mint == pc_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub user_pc_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_lp_account.mint == lp_mint.key() @ DexError::InvalidUserTokenMint,
+        constraint = user_lp_account.owner == user_authority.key() @ DexError::InvalidUserTokenOwner
+    )]
+    pub user_lp_account: InterfaceAccount<'info, TokenAccount>,
 
     pub user_authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct SwapBaseIn<'info> {
-    #[account(seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
+pub struct Observe<'info> {
+    pub amm_info: Account<'info, AmmInfo>,
+}
+
+/// `observe`의 반환값
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct Observation {
+    pub price_coin_cumulative: u128,
+    pub price_pc_cumulative: u128,
+    pub timestamp: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    #[account(mut, has_one = admin @ DexError::Unauthorized)]
+    pub amm_info: Account<'info, AmmInfo>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPoolStatus<'info> {
+    #[account(mut, has_one = admin @ DexError::Unauthorized)]
+    pub amm_info: Account<'info, AmmInfo>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePoolParams<'info> {
+    #[account(mut, has_one = admin @ DexError::Unauthorized)]
+    pub amm_info: Account<'info, AmmInfo>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RampAmp<'info> {
+    #[account(mut, has_one = admin @ DexError::Unauthorized)]
+    pub amm_info: Account<'info, AmmInfo>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StopRampAmp<'info> {
+    #[account(mut, has_one = admin @ DexError::Unauthorized)]
+    pub amm_info: Account<'info, AmmInfo>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(mut, has_one = admin @ DexError::Unauthorized)]
+    pub amm_info: Account<'info, AmmInfo>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, has_one = pending_admin @ DexError::Unauthorized)]
+    pub amm_info: Account<'info, AmmInfo>,
+    pub pending_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump,
+        has_one = admin @ DexError::Unauthorized,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault
+    )]
     pub amm_info: Account<'info, AmmInfo>,
     /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
     #[account(seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
     pub amm_authority: AccountInfo<'info>,
 
-    pub coin_mint: Account<'info, Mint>,
-    pub pc_mint: Account<'info, Mint>,
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    pub pc_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
-    pub source_vault: Account<'info, TokenAccount>,
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
-    pub destination_vault: Account<'info, TokenAccount>,
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury_coin_account.mint == coin_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub treasury_coin_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_pc_account.mint == pc_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub treasury_pc_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    #[account(
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault
+    )]
+    pub amm_info: Account<'info, AmmInfo>,
+    /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
+    #[account(seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
+    pub amm_authority: AccountInfo<'info>,
+
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    pub pc_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
-    pub user_source_account: Account<'info, TokenAccount>,
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
-    pub user_destination_account: Account<'info, TokenAccount>,
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub user_authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    #[account(
+        mut,
+        constraint = recipient_coin_account.mint == coin_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub recipient_coin_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = recipient_pc_account.mint == pc_mint.key() @ DexError::InvalidUserTokenMint
+    )]
+    pub recipient_pc_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
-pub struct Withdraw<'info> {
-    #[account(mut)]
+pub struct SyncReserves<'info> {
+    #[account(
+        mut,
+        has_one = coin_vault @ DexError::InvalidCoinVault,
+        has_one = pc_vault @ DexError::InvalidPcVault
+    )]
     pub amm_info: Account<'info, AmmInfo>,
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
+}
 
+#[derive(Accounts)]
+pub struct MigrateVaults<'info> {
+    /// CHECK: Legacy-layout AmmInfo (AmmInfo::LEGACY_LEN bytes); length and discriminator are checked in the instruction.
+    #[account(
+        mut,
+        seeds = [b"amm_info", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub amm_info: UncheckedAccount<'info>,
     /// CHECK: This is the PDA authority for the AMM, verified via seeds in the instruction.
+    #[account(seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
+    pub amm_authority: AccountInfo<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program)]
+    pub pc_mint: InterfaceAccount<'info, Mint>,
+
+    // 이전 버전의 mint 단위 vault — 풀에 기록된 주소와 일치해야 한다
     #[account(
-        seeds = [b"amm_authority", coin_mint.key().as_ref(), pc_mint.key().as_ref()],
+        mut,
+        seeds = [b"coin_vault", coin_mint.key().as_ref()],
+        bump,
+        token::mint = coin_mint,
+        token::authority = amm_authority
+    )]
+    pub legacy_coin_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"pc_vault", pc_mint.key().as_ref()],
+        bump,
+        token::mint = pc_mint,
+        token::authority = amm_authority
+    )]
+    pub legacy_pc_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        token::mint = coin_mint,
+        token::authority = amm_authority,
+        seeds = [b"coin_vault", amm_info.key().as_ref()],
         bump
     )]
-    pub amm_authority: AccountInfo<'info>,
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = admin,
+        token::mint = pc_mint,
+        token::authority = amm_authority,
+        seeds = [b"pc_vault", amm_info.key().as_ref()],
+        bump
+    )]
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub coin_mint: Account<'info, Mint>,
-    pub pc_mint: Account<'info, Mint>,
-    #[account(mut)]
-    pub lp_mint: Account<'info, Mint>,
+    #[account(seeds = [b"lp_mint", coin_mint.key().as_ref(), pc_mint.key().as_ref()], bump)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    // 이전 버전에는 없던 MINIMUM_LIQUIDITY 보관 계정
+    #[account(
+        init,
+        payer = admin,
+        token::mint = lp_mint,
+        token::authority = amm_authority,
+        seeds = [b"locked_lp", lp_mint.key().as_ref()],
+        bump
+    )]
+    pub locked_lp_vault: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub coin_vault: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub pc_vault: Account<'info, TokenAccount>,
+    // 이전 풀에는 admin이 없다 — config admin만 옮길 수 있다
+    #[account(mut, seeds = [b"amm_config"], bump, has_one = admin @ DexError::Unauthorized)]
+    pub amm_config: Account<'info, AmmConfig>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = PoolRegistry::LEN,
+        seeds = [b"pool_registry".as_ref(), &amm_config.registry_page().to_le_bytes()],
+        bump
+    )]
+    pub pool_registry: Account<'info, PoolRegistry>,
 
     #[account(mut)]
-    pub user_coin_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub user_pc_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub user_lp_account: Account<'info, TokenAccount>,
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
-    pub user_authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+// 인덱서용 이벤트. 수량은 vault 기준, reserve/LP 공급량은 instruction 처리 후 값이다.
+
+#[event]
+pub struct PoolInitialized {
+    pub amm_info: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub admin: Pubkey,
+    pub curve_type: CurveType,
+    pub amp: u64,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub open_time: u64,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub amm_info: Pubkey,
+    pub user: Pubkey,
+    pub coin_amount: u64,
+    pub pc_amount: u64,
+    pub lp_amount: u64,
+    pub coin_reserve: u64,
+    pub pc_reserve: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct Swap {
+    pub amm_info: Pubkey,
+    pub user: Pubkey,
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub coin_reserve: u64,
+    pub pc_reserve: u64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub amm_info: Pubkey,
+    pub user: Pubkey,
+    pub lp_amount: u64,
+    pub coin_amount: u64,
+    pub pc_amount: u64,
+    pub coin_reserve: u64,
+    pub pc_reserve: u64,
+    pub lp_supply: u64,
+}
+
+#[event]
+pub struct Skimmed {
+    pub amm_info: Pubkey,
+    pub coin_amount: u64,
+    pub pc_amount: u64,
+}
+
+#[event]
+pub struct ReservesSynced {
+    pub amm_info: Pubkey,
+    pub coin_reserve: u64,
+    pub pc_reserve: u64,
+}
+
+#[event]
+pub struct StatusChanged {
+    pub amm_info: Pubkey,
+    pub old_status: PoolStatus,
+    pub new_status: PoolStatus,
+}
+
+#[event]
+pub struct PoolParamsUpdated {
+    pub amm_info: Pubkey,
+    pub params: PoolParams,
 }
 
 #[error_code]
@@ -549,4 +2970,257 @@ pub enum DexError {
     PoolNotOpen,
     #[msg("No liquidity")]
     NoLiquidity,
+    #[msg("Invalid fee tier")]
+    InvalidFeeTier,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Vault does not belong to this pool")]
+    InvalidVault,
+    #[msg("Protocol fee exceeds PROTOCOL_FEE_DENOMINATOR")]
+    InvalidProtocolFee,
+    #[msg("Invalid pool status")]
+    InvalidStatus,
+    #[msg("Invalid pool status transition")]
+    InvalidStatusTransition,
+    #[msg("Signer is not the pool admin")]
+    Unauthorized,
+    #[msg("Initial liquidity must exceed MINIMUM_LIQUIDITY")]
+    InsufficientInitialLiquidity,
+    #[msg("Coin mint does not match the pool")]
+    InvalidCoinMint,
+    #[msg("Pc mint does not match the pool")]
+    InvalidPcMint,
+    #[msg("Coin vault does not match the pool")]
+    InvalidCoinVault,
+    #[msg("Pc vault does not match the pool")]
+    InvalidPcVault,
+    #[msg("LP mint does not match the pool")]
+    InvalidLpMint,
+    #[msg("User token account has the wrong mint")]
+    InvalidUserTokenMint,
+    #[msg("User token account is not owned by the signer")]
+    InvalidUserTokenOwner,
+    #[msg("Transaction deadline exceeded")]
+    DeadlineExceeded,
+    #[msg("Flash swap callback program is not allowed")]
+    InvalidCallbackProgram,
+    #[msg("Flash swap was not repaid with fees")]
+    FlashSwapNotRepaid,
+    #[msg("Invalid curve type")]
+    InvalidCurveType,
+    #[msg("Invalid amplification coefficient")]
+    InvalidAmp,
+    #[msg("Invalid amp ramp")]
+    InvalidRamp,
+    #[msg("StableSwap pools require mints with equal decimals")]
+    DecimalsMismatch,
+    #[msg("StableSwap math did not converge")]
+    CurveNotConverged,
+    #[msg("Vault balance is below the pool reserves")]
+    VaultBelowReserves,
+    #[msg("Swap is below the pool's minimum size")]
+    BelowMinSize,
+    #[msg("Execution price is outside the pool's price band")]
+    PriceOutOfBand,
+    #[msg("Price impact exceeds the pool's maximum")]
+    PriceImpactTooHigh,
+    #[msg("Invalid pool parameters")]
+    InvalidPoolParams,
+    #[msg("Coin mint must sort before pc mint")]
+    MintsNotSorted,
+    #[msg("Fee receiver does not match the config")]
+    InvalidFeeReceiver,
+    #[msg("LP supply or locked LP changed during the flash swap callback")]
+    FlashSwapLpChanged,
+    #[msg("Pool already uses the current account layout")]
+    NotLegacyPool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(coin_reserve: u64, pc_reserve: u64) -> AmmInfo {
+        let mut pool = AmmInfo::try_deserialize_unchecked(&mut &[0u8; AmmInfo::LEN][..]).unwrap();
+        pool.trade_fee_numerator = 30;
+        pool.trade_fee_denominator = FEE_DENOMINATOR;
+        pool.pool_coin_amount = coin_reserve;
+        pool.pool_pc_amount = pc_reserve;
+        pool
+    }
+
+    #[test]
+    fn status_permissions_follow_the_table() {
+        use PoolStatus::*;
+        for (status, deposit, swap, withdraw) in [
+            (Uninitialized, false, false, false),
+            (DepositOnly, true, false, true),
+            (WithdrawOnly, false, false, true),
+            (Trading, true, true, true),
+            (Disabled, false, false, false),
+        ] {
+            assert_eq!(PoolStatus::from_u64(status as u64).unwrap(), status);
+            assert_eq!(
+                (
+                    status.can_deposit(),
+                    status.can_swap(),
+                    status.can_withdraw()
+                ),
+                (deposit, swap, withdraw),
+                "{status:?}"
+            );
+        }
+        assert!(PoolStatus::from_u64(5).is_err());
+    }
+
+    #[test]
+    fn disabled_is_terminal() {
+        use PoolStatus::*;
+        assert!(DepositOnly.can_transition_to(Trading));
+        assert!(Trading.can_transition_to(WithdrawOnly));
+        assert!(Trading.can_transition_to(Disabled));
+        assert!(!Disabled.can_transition_to(Trading));
+        assert!(!Trading.can_transition_to(Trading));
+        assert!(!Trading.can_transition_to(Uninitialized));
+        assert!(!Uninitialized.can_transition_to(Trading));
+    }
+
+    #[test]
+    fn legacy_layout_is_a_prefix_of_amm_info() {
+        let mut legacy = pool(1_000, 2_000);
+        legacy.amm_pc_account = Pubkey::new_unique();
+        legacy.trade_fee_numerator = 30;
+        let mut data = Vec::new();
+        legacy.try_serialize(&mut data).unwrap();
+
+        // migrate_vaults처럼 이전 크기로 자른 계정을 늘리면 새 필드만 0이 된다
+        data.truncate(AmmInfo::LEGACY_LEN);
+        data.resize(AmmInfo::LEN, 0);
+        let migrated = AmmInfo::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(migrated.reserves(), (1_000, 2_000));
+        assert_eq!(migrated.amm_pc_account, legacy.amm_pc_account);
+        assert_eq!(migrated.trade_fee_numerator, 0);
+    }
+
+    #[test]
+    fn price_cumulative_accumulates_the_price_before_the_update() {
+        let mut pool = pool(1_000_000, 2_000_000);
+        pool.last_update_timestamp = 100;
+        pool.update_price_cumulative(1_000_000, 2_000_000, 110);
+        // coin 가격 = pc/coin = 2, pc 가격 = 1/2 (Q64.64) × 10초
+        assert_eq!(pool.price_coin_cumulative, (2u128 << 64) * 10);
+        assert_eq!(pool.price_pc_cumulative, (1u128 << 63) * 10);
+        assert_eq!(pool.last_update_timestamp, 110);
+
+        // 같은 시점이나 과거 시점은 누적하지 않는다
+        pool.update_price_cumulative(1_000_000, 2_000_000, 110);
+        pool.update_price_cumulative(1_000_000, 2_000_000, 105);
+        assert_eq!(pool.price_coin_cumulative, (2u128 << 64) * 10);
+        assert_eq!(pool.last_update_timestamp, 110);
+
+        // 빈 풀은 시간만 진행한다
+        pool.update_price_cumulative(0, 0, 120);
+        assert_eq!(pool.price_coin_cumulative, (2u128 << 64) * 10);
+        assert_eq!(pool.last_update_timestamp, 120);
+    }
+
+    #[test]
+    fn price_cumulative_wraps() {
+        let mut pool = pool(1_000_000, 1_000_000);
+        pool.price_coin_cumulative = u128::MAX;
+        pool.update_price_cumulative(1_000_000, 1_000_000, 1);
+        // 소비자는 wrapping 차분으로 TWAP을 구한다
+        assert_eq!(pool.price_coin_cumulative, (1u128 << 64) - 1);
+        assert_eq!(
+            pool.price_coin_cumulative.wrapping_sub(u128::MAX),
+            1u128 << 64
+        );
+    }
+
+    #[test]
+    fn protocol_fee_is_a_share_of_the_trade_fee() {
+        let mut pool = pool(1_000_000, 1_000_000);
+        assert_eq!(pool.protocol_fee(100_000), 0);
+        // 거래 수수료 300 중 20%
+        pool.protocol_fee_numerator = 2_000;
+        assert_eq!(pool.protocol_fee(100_000), 60);
+        pool.protocol_fee_numerator = PROTOCOL_FEE_DENOMINATOR;
+        assert_eq!(pool.protocol_fee(100_000), 300);
+    }
+
+    #[test]
+    fn apply_swap_keeps_the_protocol_fee_out_of_the_reserves() {
+        let mut pool = pool(1_000_000, 1_000_000);
+        pool.protocol_fee_numerator = 2_000;
+        pool.apply_swap(SwapDirection::CoinToPc, 100_000, 90_000)
+            .unwrap();
+        assert_eq!(pool.protocol_fee_coin, 60);
+        assert_eq!(pool.protocol_fee_pc, 0);
+        assert_eq!(pool.reserves(), (1_099_940, 910_000));
+        // vault에는 유동성과 수거 전 프로토콜 수수료가 함께 있다
+        assert_eq!(
+            pool.vault_liquidity(1_100_000, 910_000).unwrap(),
+            (1_099_940, 910_000)
+        );
+        assert_eq!(pool.surplus(1_100_000, 910_000).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn flash_net_swap_is_the_one_sided_flow() {
+        // 같은 토큰으로 원금 + 수수료 상환은 스왑이 아니다
+        assert_eq!(flash_net_swap((1_000, 0), (1_004, 0)), None);
+        assert_eq!(flash_net_swap((1_000, 2_000), (1_004, 2_007)), None);
+        // coin을 빌리고 pc로 갚으면 pc → coin 스왑
+        assert_eq!(
+            flash_net_swap((1_000, 0), (0, 1_010)),
+            Some((SwapDirection::PcToCoin, 1_010, 1_000))
+        );
+        assert_eq!(
+            flash_net_swap((1_000, 500), (200, 1_510)),
+            Some((SwapDirection::PcToCoin, 1_010, 800))
+        );
+        assert_eq!(
+            flash_net_swap((0, 1_000), (1_010, 0)),
+            Some((SwapDirection::CoinToPc, 1_010, 1_000))
+        );
+    }
+
+    #[test]
+    fn price_band_uses_the_stable_swap_marginal_price() {
+        // 잔고 비율(2.0)이 아니라 곡선의 한계 가격(약 1.0) 기준으로 밴드를 검사한다
+        let mut pool = pool(1_000_000, 2_000_000);
+        pool.curve_type = CurveType::StableSwap as u64;
+        pool.initial_amp = 1_000;
+        pool.target_amp = 1_000;
+        pool.min_price_multiplier = 9_900;
+        pool.max_price_multiplier = 10_100;
+        let amount_out = pool
+            .swap_amount_out(1_000, 1_000_000, 2_000_000, 0)
+            .unwrap();
+        pool.check_trade(
+            SwapDirection::CoinToPc,
+            1_000,
+            amount_out,
+            1_000_000,
+            2_000_000,
+            0,
+        )
+        .unwrap();
+        assert!(pool
+            .check_trade(
+                SwapDirection::CoinToPc,
+                1_000,
+                2_000,
+                1_000_000,
+                2_000_000,
+                0
+            )
+            .is_err());
+
+        // 같은 스왑이 한계 가격을 거의 움직이지 않는다
+        pool.vol_max_cut_ratio = 10;
+        pool.apply_swap(SwapDirection::CoinToPc, 1_000, amount_out)
+            .unwrap();
+        pool.check_price_impact(1_000_000, 2_000_000, 0).unwrap();
+    }
 }
@@ -0,0 +1,319 @@
+//! 오버플로 검사와 반올림 방향이 명시된 정수 연산.
+//!
+//! 반올림 원칙: 사용자에게 나가는 양(스왑 출력, 출금, 발행 LP)은 내림, 사용자가 내는 양
+//! (스왑 입력, 페어 공급량, 수수료)은 올림 — 오차는 항상 풀에 남는다. u128 곱이 넘칠 수
+//! 있는 곳은 `U256`으로 계산하고, u64로 돌아올 때는 항상 범위를 검사한다.
+
+use crate::{DexError, U256};
+use anchor_lang::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// `a * b / denominator` (중간값 u128)
+pub fn mul_div(a: u64, b: u64, denominator: u64, rounding: Rounding) -> Result<u64> {
+    require!(denominator > 0, DexError::MathOverflow);
+    let product = a as u128 * b as u128;
+    let quotient = match rounding {
+        Rounding::Down => product / denominator as u128,
+        Rounding::Up => product.div_ceil(denominator as u128),
+    };
+    u64::try_from(quotient).map_err(|_| error!(DexError::MathOverflow))
+}
+
+/// `numerator / denominator`를 u64로 (중간값 U256)
+pub fn div_u256(numerator: U256, denominator: U256, rounding: Rounding) -> Result<u64> {
+    require!(!denominator.is_zero(), DexError::MathOverflow);
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    let quotient = match rounding {
+        Rounding::Up if !remainder.is_zero() => quotient + 1,
+        _ => quotient,
+    };
+    require!(quotient <= U256::from(u64::MAX), DexError::MathOverflow);
+    Ok(quotient.as_u64())
+}
+
+/// 내림 제곱근. sqrt(u128::MAX) < 2^64 이므로 실패하지 않는다.
+pub fn sqrt(y: u128) -> u64 {
+    if y == 0 {
+        return 0;
+    }
+    let mut z = y;
+    let mut x = y / 2 + 1;
+    while x < z {
+        z = x;
+        x = (y / x + x) / 2;
+    }
+    z as u64
+}
+
+/// 한쪽 `amount`에 맞춰 반대쪽에서 받을 양 (사용자가 내므로 올림)
+pub fn paired_amount(amount: u64, reserve_base: u64, reserve_paired: u64) -> Result<u64> {
+    mul_div(amount, reserve_paired, reserve_base, Rounding::Up)
+}
+
+/// (coin, pc) 공급에 대해 발행할 LP — 두 비율 중 작은 쪽, 내림
+pub fn lp_for_deposit(
+    coin_amount: u64,
+    pc_amount: u64,
+    coin_reserve: u64,
+    pc_reserve: u64,
+    lp_supply: u64,
+) -> Result<u64> {
+    Ok(std::cmp::min(
+        mul_div(coin_amount, lp_supply, coin_reserve, Rounding::Down)?,
+        mul_div(pc_amount, lp_supply, pc_reserve, Rounding::Down)?,
+    ))
+}
+
+/// LP `amount` 소각 시 돌려줄 (coin, pc) — 내림
+pub fn withdraw_amounts(
+    amount: u64,
+    coin_reserve: u64,
+    pc_reserve: u64,
+    lp_supply: u64,
+) -> Result<(u64, u64)> {
+    require!(amount <= lp_supply, DexError::InvalidAmount);
+    Ok((
+        mul_div(coin_reserve, amount, lp_supply, Rounding::Down)?,
+        mul_div(pc_reserve, amount, lp_supply, Rounding::Down)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_amount_in, calculate_amount_out, MINIMUM_LIQUIDITY};
+    use proptest::prelude::*;
+
+    #[test]
+    fn mul_div_rounds_in_the_requested_direction() {
+        assert_eq!(mul_div(7, 3, 2, Rounding::Down).unwrap(), 10);
+        assert_eq!(mul_div(7, 3, 2, Rounding::Up).unwrap(), 11);
+        assert_eq!(mul_div(8, 3, 2, Rounding::Up).unwrap(), 12);
+        assert!(mul_div(u64::MAX, 2, 1, Rounding::Down).is_err());
+        assert!(mul_div(1, 1, 0, Rounding::Down).is_err());
+    }
+
+    #[test]
+    fn div_u256_checks_the_u64_range() {
+        let big = U256::from(u64::MAX) * U256::from(u64::MAX);
+        assert_eq!(
+            div_u256(big, U256::from(u64::MAX), Rounding::Up).unwrap(),
+            u64::MAX
+        );
+        assert!(div_u256(big, U256::from(2u64), Rounding::Down).is_err());
+        assert!(div_u256(big, U256::zero(), Rounding::Down).is_err());
+    }
+
+    #[test]
+    fn amount_out_does_not_overflow_on_large_reserves() {
+        let out = calculate_amount_out(u64::MAX / 2, u64::MAX, u64::MAX, 30, 10_000).unwrap();
+        assert!(out > 0 && out < u64::MAX / 2);
+        let amount_in = calculate_amount_in(u64::MAX / 4, u64::MAX, u64::MAX, 30, 10_000);
+        assert!(amount_in.is_err() || amount_in.unwrap() > u64::MAX / 4);
+    }
+
+    #[test]
+    fn higher_fee_tiers_pay_out_less() {
+        let outputs: Vec<u64> = crate::FEE_TIERS_BPS
+            .iter()
+            .map(|&fee| calculate_amount_out(10_000, 1_000_000, 1_000_000, fee, 10_000).unwrap())
+            .collect();
+        assert_eq!(outputs, vec![9_900, 9_896, 9_871, 9_802]);
+        // 수수료가 분모 이상이면 거부
+        assert!(calculate_amount_out(10_000, 1_000_000, 1_000_000, 10_000, 10_000).is_err());
+        assert!(calculate_amount_in(10_000, 1_000_000, 1_000_000, 10_000, 10_000).is_err());
+    }
+
+    #[test]
+    fn amount_in_rejects_draining_the_output_reserve() {
+        assert!(calculate_amount_in(1_000, 1_000, 1_000, 30, 10_000).is_err());
+        assert!(calculate_amount_in(1_001, 1_000, 1_000, 30, 10_000).is_err());
+        assert!(calculate_amount_in(0, 1_000, 1_000, 30, 10_000).is_err());
+        assert_eq!(
+            calculate_amount_in(10_000, 1_000_000, 1_000_000, 30, 10_000).unwrap(),
+            10_132
+        );
+    }
+
+    /// 풀 상태 (x, y, LP 공급량)
+    #[derive(Clone, Copy, Debug)]
+    struct Pool {
+        x: u64,
+        y: u64,
+        supply: u64,
+    }
+
+    impl Pool {
+        fn k(&self) -> U256 {
+            U256::from(self.x) * U256::from(self.y)
+        }
+
+        /// LP 1단위당 k가 줄지 않았는지: k1 / s1² >= k0 / s0²
+        fn k_per_share_kept(&self, before: &Pool) -> bool {
+            let supply = |pool: &Pool| U256::from(pool.supply) * U256::from(pool.supply);
+            self.k() * supply(before) >= before.k() * supply(self)
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    enum Op {
+        SwapIn { coin_to_pc: bool, amount: u64 },
+        SwapOut { coin_to_pc: bool, amount: u64 },
+        Deposit { coin_base: bool, amount: u64 },
+        Withdraw { amount: u64 },
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (any::<bool>(), 1..u64::MAX / 4)
+                .prop_map(|(coin_to_pc, amount)| Op::SwapIn { coin_to_pc, amount }),
+            (any::<bool>(), 1..u64::MAX / 4)
+                .prop_map(|(coin_to_pc, amount)| Op::SwapOut { coin_to_pc, amount }),
+            (any::<bool>(), 1..u64::MAX / 4)
+                .prop_map(|(coin_base, amount)| Op::Deposit { coin_base, amount }),
+            (1..u64::MAX).prop_map(|amount| Op::Withdraw { amount }),
+        ]
+    }
+
+    fn apply(pool: &mut Pool, op: &Op, fee_bps: u64) {
+        let before = *pool;
+        match *op {
+            Op::SwapIn { coin_to_pc, amount } | Op::SwapOut { coin_to_pc, amount } => {
+                let (reserve_in, reserve_out) = if coin_to_pc {
+                    (pool.x, pool.y)
+                } else {
+                    (pool.y, pool.x)
+                };
+                // 스케일을 풀 크기에 맞춘다
+                let amount = amount % reserve_out.max(2) + 1;
+                let result = if matches!(op, Op::SwapIn { .. }) {
+                    calculate_amount_out(amount, reserve_in, reserve_out, fee_bps, 10_000)
+                        .map(|out| (amount, out))
+                } else {
+                    calculate_amount_in(amount, reserve_in, reserve_out, fee_bps, 10_000)
+                        .map(|amount_in| (amount_in, amount))
+                };
+                let Ok((amount_in, amount_out)) = result else {
+                    return;
+                };
+                let Some(new_in) = reserve_in.checked_add(amount_in) else {
+                    return;
+                };
+                let new_out = reserve_out - amount_out;
+                (pool.x, pool.y) = if coin_to_pc {
+                    (new_in, new_out)
+                } else {
+                    (new_out, new_in)
+                };
+                assert!(pool.k() >= before.k(), "swap {op:?} on {before:?}");
+            }
+            Op::Deposit { coin_base, amount } => {
+                let (coin, pc) = if coin_base {
+                    let coin = amount % pool.x.max(2) + 1;
+                    let Ok(pc) = paired_amount(coin, pool.x, pool.y) else {
+                        return;
+                    };
+                    (coin, pc)
+                } else {
+                    let pc = amount % pool.y.max(2) + 1;
+                    let Ok(coin) = paired_amount(pc, pool.y, pool.x) else {
+                        return;
+                    };
+                    (coin, pc)
+                };
+                let Ok(lp) = lp_for_deposit(coin, pc, pool.x, pool.y, pool.supply) else {
+                    return;
+                };
+                let (Some(x), Some(y), Some(supply)) = (
+                    pool.x.checked_add(coin),
+                    pool.y.checked_add(pc),
+                    pool.supply.checked_add(lp),
+                ) else {
+                    return;
+                };
+                if lp == 0 {
+                    return;
+                }
+                *pool = Pool { x, y, supply };
+                assert!(
+                    pool.k_per_share_kept(&before),
+                    "deposit {op:?} on {before:?}"
+                );
+            }
+            Op::Withdraw { amount } => {
+                // MINIMUM_LIQUIDITY는 잠겨 있으므로 그 이상만 소각 가능
+                let burnable = pool.supply - MINIMUM_LIQUIDITY;
+                if burnable == 0 {
+                    return;
+                }
+                let amount = amount % burnable + 1;
+                let (coin, pc) = withdraw_amounts(amount, pool.x, pool.y, pool.supply).unwrap();
+                *pool = Pool {
+                    x: pool.x - coin,
+                    y: pool.y - pc,
+                    supply: pool.supply - amount,
+                };
+                assert!(pool.x > 0 && pool.y > 0, "withdraw drained {before:?}");
+                assert!(
+                    pool.k_per_share_kept(&before),
+                    "withdraw {op:?} on {before:?}"
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn sqrt_is_the_floor_root(y in any::<u128>()) {
+            let root = sqrt(y) as u128;
+            prop_assert!(root * root <= y);
+            prop_assert!((root + 1).checked_mul(root + 1).is_none_or(|next| next > y));
+        }
+
+        #[test]
+        fn mul_div_up_is_down_plus_remainder(a in any::<u64>(), b in any::<u64>(), d in 1..u64::MAX) {
+            if let (Ok(down), Ok(up)) = (mul_div(a, b, d, Rounding::Down), mul_div(a, b, d, Rounding::Up)) {
+                let exact = (a as u128 * b as u128).is_multiple_of(d as u128);
+                prop_assert_eq!(up, if exact { down } else { down + 1 });
+            }
+        }
+
+        #[test]
+        fn amount_in_is_the_minimum_for_the_exact_output(
+            reserve_in in 1_000..1_000_000_000_000u64,
+            reserve_out in 1_000..1_000_000_000_000u64,
+            amount_out in 1..u64::MAX,
+            fee_bps in prop::sample::select(crate::FEE_TIERS_BPS.to_vec()),
+        ) {
+            let amount_out = amount_out % (reserve_out - 1) + 1;
+            // reserve_out을 거의 다 빼는 입력은 u64를 넘을 수 있다
+            let amount_in = calculate_amount_in(amount_out, reserve_in, reserve_out, fee_bps, 10_000);
+            prop_assume!(amount_in.is_ok());
+            let amount_in = amount_in.unwrap();
+            // 요청한 출력을 덮고, 1 적게 넣으면 덮지 못한다
+            prop_assert!(calculate_amount_out(amount_in, reserve_in, reserve_out, fee_bps, 10_000).unwrap() >= amount_out);
+            prop_assert!(!matches!(
+                calculate_amount_out(amount_in - 1, reserve_in, reserve_out, fee_bps, 10_000),
+                Ok(out) if out >= amount_out
+            ));
+        }
+
+        #[test]
+        fn k_never_decreases(
+            x in 1_000_000..1_000_000_000_000_000u64,
+            y in 1_000_000..1_000_000_000_000_000u64,
+            fee_bps in prop::sample::select(crate::FEE_TIERS_BPS.to_vec()),
+            ops in prop::collection::vec(op(), 1..40),
+        ) {
+            let mut pool = Pool { x, y, supply: sqrt(x as u128 * y as u128) };
+            for op in &ops {
+                apply(&mut pool, op, fee_bps);
+            }
+        }
+    }
+}
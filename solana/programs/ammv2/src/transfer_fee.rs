@@ -0,0 +1,43 @@
+//! Token-2022 transfer-fee 확장 처리.
+//!
+//! 수수료가 있는 mint는 보낸 양과 받는 쪽에 도착하는 양이 다르다. 풀 계산은 항상
+//! vault에 실제로 들어오거나 나가는 양을 기준으로 하고, 사용자 쪽 한도(min/max)는
+//! 사용자가 실제로 내거나 받는 양과 비교한다. 확장이 없는 mint(기존 SPL Token 포함)는 수수료 0.
+
+use crate::DexError;
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::{
+    TransferFee, TransferFeeConfig,
+};
+use anchor_spl::token_interface::{get_mint_extension_data, Mint, Token2022};
+
+/// 현재 epoch에 적용되는 transfer fee 설정 (없으면 `None`)
+fn epoch_fee(mint: &InterfaceAccount<Mint>) -> Result<Option<TransferFee>> {
+    let info = mint.to_account_info();
+    if *info.owner != Token2022::id() {
+        return Ok(None);
+    }
+    match get_mint_extension_data::<TransferFeeConfig>(&info) {
+        Ok(config) => Ok(Some(*config.get_epoch_fee(Clock::get()?.epoch))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// `amount`를 보냈을 때 받는 쪽에 도착하는 양
+pub fn amount_after_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let Some(fee) = epoch_fee(mint)? else {
+        return Ok(amount);
+    };
+    let fee = fee.calculate_fee(amount).ok_or(DexError::MathOverflow)?;
+    Ok(amount.saturating_sub(fee))
+}
+
+/// 받는 쪽에 정확히 `amount`가 도착하도록 보내야 하는 양
+pub fn amount_before_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let Some(fee) = epoch_fee(mint)? else {
+        return Ok(amount);
+    };
+    Ok(fee
+        .calculate_pre_fee_amount(amount)
+        .ok_or(DexError::MathOverflow)?)
+}
@@ -0,0 +1,834 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+pub mod math;
+pub mod state;
+
+use math::{MAX_SQRT_PRICE_X64, MAX_TICK, MIN_SQRT_PRICE_X64, MIN_TICK};
+pub use state::*;
+
+declare_id!("BjbbmxqQTx8AwtiFK39Zg9JAiHLpT97C9LnG2vVMA982");
+
+// 스왑 수수료: FEE_TIERS_BPS[i] / FEE_DENOMINATOR, tick 간격은 TICK_SPACINGS[i]
+pub const FEE_DENOMINATOR: u64 = 10_000;
+pub const FEE_TIERS_BPS: [u64; 4] = [1, 5, 30, 100];
+pub const TICK_SPACINGS: [u16; 4] = [1, 10, 60, 200];
+
+/// Uniswap v3 방식 concentrated-liquidity 풀.
+///
+/// vault/authority PDA는 풀마다 따로 둔다:
+/// amm_authority = [b"amm_authority", pool_state], vault = [b"coin_vault"/b"pc_vault", pool_state].
+/// mint 쌍은 ammv2와 같이 주소 오름차순(coin < pc)으로만 받는다.
+/// 같은 mint 쌍에 수수료 티어별로 풀을 하나씩 둘 수 있다:
+/// pool_state = [b"pool_state", coin_mint, pc_mint, fee_bps (u64 LE)].
+#[program]
+pub mod clmm {
+    use super::*;
+
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        fee_bps: u64,
+        sqrt_price_x64: u128,
+    ) -> Result<()> {
+        let tier = FEE_TIERS_BPS
+            .iter()
+            .position(|&fee| fee == fee_bps)
+            .ok_or(ClmmError::InvalidFeeTier)?;
+        require!(
+            ctx.accounts.coin_mint.key() < ctx.accounts.pc_mint.key(),
+            ClmmError::MintsNotSorted
+        );
+
+        let pool = &mut ctx.accounts.pool_state;
+        pool.coin_mint = ctx.accounts.coin_mint.key();
+        pool.pc_mint = ctx.accounts.pc_mint.key();
+        pool.coin_vault = ctx.accounts.coin_vault.key();
+        pool.pc_vault = ctx.accounts.pc_vault.key();
+
+        pool.tick_spacing = TICK_SPACINGS[tier];
+        pool.trade_fee_numerator = fee_bps;
+        pool.trade_fee_denominator = FEE_DENOMINATOR;
+
+        // 시작 가격 (pc / coin 의 sqrt, Q64.64)
+        pool.sqrt_price_x64 = sqrt_price_x64;
+        pool.tick_current = math::tick_at_sqrt_price(sqrt_price_x64)?;
+        pool.liquidity = 0;
+
+        pool.fee_growth_global_coin_x64 = 0;
+        pool.fee_growth_global_pc_x64 = 0;
+
+        msg!(
+            "CLMM pool initialized with coin: {} and pc: {} at tick {}",
+            pool.coin_mint,
+            pool.pc_mint,
+            pool.tick_current
+        );
+        Ok(())
+    }
+
+    /// `start_tick_index`부터 TICK_ARRAY_SIZE 개 tick을 담는 array 생성 (누구나 호출 가능)
+    pub fn initialize_tick_array(
+        ctx: Context<InitializeTickArray>,
+        start_tick_index: i32,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool_state;
+        require!(
+            start_tick_index == pool.tick_array_start_index(start_tick_index)
+                && start_tick_index >= pool.tick_array_start_index(MIN_TICK)
+                && start_tick_index <= MAX_TICK,
+            ClmmError::InvalidTickArray
+        );
+
+        let mut tick_array = ctx.accounts.tick_array.load_init()?;
+        tick_array.pool = pool.key();
+        tick_array.start_tick_index = start_tick_index;
+
+        msg!("Tick array created at {}", start_tick_index);
+        Ok(())
+    }
+
+    /// [tick_lower, tick_upper) 구간의 빈 포지션 생성. 유동성은 increase_liquidity로 넣는다.
+    pub fn open_position(
+        ctx: Context<OpenPosition>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool_state;
+        pool.check_tick(tick_lower)?;
+        pool.check_tick(tick_upper)?;
+        require!(tick_lower < tick_upper, ClmmError::InvalidTickRange);
+
+        let position = &mut ctx.accounts.position;
+        position.pool = pool.key();
+        position.owner = ctx.accounts.owner.key();
+        position.tick_lower = tick_lower;
+        position.tick_upper = tick_upper;
+        position.liquidity = 0;
+        position.fee_growth_inside_coin_last_x64 = 0;
+        position.fee_growth_inside_pc_last_x64 = 0;
+        position.tokens_owed_coin = 0;
+        position.tokens_owed_pc = 0;
+
+        msg!("Position opened: [{}, {})", tick_lower, tick_upper);
+        Ok(())
+    }
+
+    /// 포지션에 `liquidity`를 더한다. 필요한 토큰 양은 올림 계산 후 최대값과 비교.
+    pub fn increase_liquidity(
+        ctx: Context<IncreaseLiquidity>,
+        liquidity: u128,
+        amount_coin_max: u64,
+        amount_pc_max: u64,
+    ) -> Result<()> {
+        require!(liquidity > 0, ClmmError::InvalidAmount);
+        let liquidity_delta = i128::try_from(liquidity).map_err(|_| ClmmError::MathOverflow)?;
+
+        let pool_key = ctx.accounts.pool_state.key();
+        let (amount_coin, amount_pc) = modify_position(
+            &mut ctx.accounts.pool_state,
+            pool_key,
+            &mut ctx.accounts.position,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+            liquidity_delta,
+        )?;
+        require!(
+            amount_coin <= amount_coin_max && amount_pc <= amount_pc_max,
+            ClmmError::SlippageExceeded
+        );
+
+        // 사용자 → Vault
+        for (from, to, amount) in [
+            (
+                &ctx.accounts.user_coin_account,
+                &ctx.accounts.coin_vault,
+                amount_coin,
+            ),
+            (
+                &ctx.accounts.user_pc_account,
+                &ctx.accounts.pc_vault,
+                amount_pc,
+            ),
+        ] {
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: from.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: ctx.accounts.owner.to_account_info(),
+                        },
+                    ),
+                    amount,
+                )?;
+            }
+        }
+
+        msg!(
+            "Liquidity increased by {}: {} coin, {} pc",
+            liquidity,
+            amount_coin,
+            amount_pc
+        );
+        Ok(())
+    }
+
+    /// 포지션에서 `liquidity`를 빼고 원금과 쌓인 수수료를 함께 돌려준다.
+    /// `liquidity = 0`이면 수수료만 수령한다.
+    pub fn decrease_liquidity(
+        ctx: Context<DecreaseLiquidity>,
+        liquidity: u128,
+        amount_coin_min: u64,
+        amount_pc_min: u64,
+    ) -> Result<()> {
+        let liquidity_delta = i128::try_from(liquidity).map_err(|_| ClmmError::MathOverflow)?;
+
+        let pool_key = ctx.accounts.pool_state.key();
+        let (amount_coin, amount_pc) = modify_position(
+            &mut ctx.accounts.pool_state,
+            pool_key,
+            &mut ctx.accounts.position,
+            &ctx.accounts.tick_array_lower,
+            &ctx.accounts.tick_array_upper,
+            -liquidity_delta,
+        )?;
+        require!(
+            amount_coin >= amount_coin_min && amount_pc >= amount_pc_min,
+            ClmmError::SlippageExceeded
+        );
+
+        let position = &mut ctx.accounts.position;
+        let payout_coin = amount_coin
+            .checked_add(position.tokens_owed_coin)
+            .ok_or(ClmmError::MathOverflow)?;
+        let payout_pc = amount_pc
+            .checked_add(position.tokens_owed_pc)
+            .ok_or(ClmmError::MathOverflow)?;
+        let (fees_coin, fees_pc) = (position.tokens_owed_coin, position.tokens_owed_pc);
+        position.tokens_owed_coin = 0;
+        position.tokens_owed_pc = 0;
+
+        // PDA signer (amm_authority)
+        let pool_key = ctx.accounts.pool_state.key();
+        let seeds: &[&[u8]] = &[
+            b"amm_authority",
+            pool_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[seeds];
+
+        // Vault → 사용자
+        for (from, to, amount) in [
+            (
+                &ctx.accounts.coin_vault,
+                &ctx.accounts.user_coin_account,
+                payout_coin,
+            ),
+            (
+                &ctx.accounts.pc_vault,
+                &ctx.accounts.user_pc_account,
+                payout_pc,
+            ),
+        ] {
+            if amount > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: from.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: ctx.accounts.amm_authority.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount,
+                )?;
+            }
+        }
+
+        msg!(
+            "Liquidity decreased by {}: {} coin, {} pc (+ fees {} coin, {} pc)",
+            liquidity,
+            amount_coin,
+            amount_pc,
+            fees_coin,
+            fees_pc
+        );
+        Ok(())
+    }
+
+    /// 유동성과 미수령 수수료가 없는 포지션을 닫고 rent를 돌려받는다.
+    pub fn close_position(_ctx: Context<ClosePosition>) -> Result<()> {
+        msg!("Position closed");
+        Ok(())
+    }
+
+    /// 정확한 입력 스왑. 건널 수 있는 tick array들을 remaining accounts로 스왑 방향 순서대로
+    /// 넘긴다 (첫 array는 현재 tick을 포함해야 한다). `sqrt_price_limit_x64 = 0`이면 제한 없음;
+    /// 제한 가격에 닿으면 남은 입력은 사용하지 않는다.
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Swap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        sqrt_price_limit_x64: u128,
+        direction: SwapDirection,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        if let Some(deadline) = deadline {
+            require!(
+                Clock::get()?.unix_timestamp <= deadline,
+                ClmmError::DeadlineExceeded
+            );
+        }
+        require!(amount_in > 0, ClmmError::InvalidAmount);
+
+        let pool_key = ctx.accounts.pool_state.key();
+        let pool = &mut ctx.accounts.pool_state;
+        let coin_in = direction == SwapDirection::CoinToPc;
+        let sqrt_price_limit_x64 = match sqrt_price_limit_x64 {
+            0 if coin_in => MIN_SQRT_PRICE_X64 + 1,
+            0 => MAX_SQRT_PRICE_X64 - 1,
+            limit => limit,
+        };
+        require!(
+            if coin_in {
+                sqrt_price_limit_x64 < pool.sqrt_price_x64
+                    && sqrt_price_limit_x64 > MIN_SQRT_PRICE_X64
+            } else {
+                sqrt_price_limit_x64 > pool.sqrt_price_x64
+                    && sqrt_price_limit_x64 < MAX_SQRT_PRICE_X64
+            },
+            ClmmError::InvalidPriceLimit
+        );
+
+        let tick_arrays = ctx
+            .remaining_accounts
+            .iter()
+            .map(AccountLoader::<TickArray>::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        require!(!tick_arrays.is_empty(), ClmmError::NotEnoughTickArrays);
+        {
+            let first = tick_arrays[0].load()?;
+            require_keys_eq!(first.pool, pool_key, ClmmError::InvalidTickArray);
+            require!(
+                first.start_tick_index == pool.tick_array_start_index(pool.tick_current),
+                ClmmError::InvalidTickArray
+            );
+        }
+
+        let spacing = pool.tick_spacing;
+        let span = pool.tick_array_span();
+        let mut array_index = 0;
+        let mut amount_remaining = amount_in;
+        let mut amount_out: u64 = 0;
+
+        while amount_remaining > 0 && pool.sqrt_price_x64 != sqrt_price_limit_x64 {
+            // 다음 초기화된 tick (현재 array에 없으면 다음 array로, 더 없으면 array 경계)
+            let (next_tick, initialized) = loop {
+                let array = tick_arrays[array_index].load()?;
+                let start = array.start_tick_index;
+                if let Some(tick) = array.next_initialized_tick(pool.tick_current, spacing, coin_in)
+                {
+                    break (tick, true);
+                }
+                if array_index + 1 == tick_arrays.len() {
+                    // 가격 하락 중 이미 이 array 아래로 내려왔으면 더 갈 수 없다
+                    require!(
+                        !coin_in || pool.tick_current >= start,
+                        ClmmError::NotEnoughTickArrays
+                    );
+                    let boundary = if coin_in { start } else { start + span };
+                    break (boundary.clamp(MIN_TICK, MAX_TICK), false);
+                }
+
+                array_index += 1;
+                let next = tick_arrays[array_index].load()?;
+                require_keys_eq!(next.pool, pool_key, ClmmError::InvalidTickArray);
+                require!(
+                    next.start_tick_index == if coin_in { start - span } else { start + span },
+                    ClmmError::InvalidTickArray
+                );
+            };
+
+            let sqrt_price_next_tick = math::sqrt_price_at_tick(next_tick)?;
+            let sqrt_price_target = if coin_in {
+                sqrt_price_next_tick.max(sqrt_price_limit_x64)
+            } else {
+                sqrt_price_next_tick.min(sqrt_price_limit_x64)
+            };
+            let step = math::compute_swap_step(
+                pool.sqrt_price_x64,
+                sqrt_price_target,
+                pool.liquidity,
+                amount_remaining,
+                pool.trade_fee_numerator,
+                pool.trade_fee_denominator,
+            )?;
+
+            amount_remaining -= step.amount_in + step.fee_amount;
+            amount_out = amount_out
+                .checked_add(step.amount_out)
+                .ok_or(ClmmError::MathOverflow)?;
+            // 수수료는 현재 구간의 유동성에 분배
+            let fee_growth = math::fee_growth_delta(step.fee_amount, pool.liquidity);
+            if coin_in {
+                pool.fee_growth_global_coin_x64 =
+                    pool.fee_growth_global_coin_x64.wrapping_add(fee_growth);
+            } else {
+                pool.fee_growth_global_pc_x64 =
+                    pool.fee_growth_global_pc_x64.wrapping_add(fee_growth);
+            }
+            pool.sqrt_price_x64 = step.sqrt_price_next_x64;
+
+            if step.sqrt_price_next_x64 == sqrt_price_next_tick {
+                if initialized {
+                    let mut array = tick_arrays[array_index].load_mut()?;
+                    let mut tick = array.get_tick(next_tick, spacing)?;
+                    let liquidity_net = tick.cross(
+                        pool.fee_growth_global_coin_x64,
+                        pool.fee_growth_global_pc_x64,
+                    );
+                    array.set_tick(next_tick, spacing, tick)?;
+                    pool.liquidity = math::add_liquidity_delta(
+                        pool.liquidity,
+                        if coin_in {
+                            -liquidity_net
+                        } else {
+                            liquidity_net
+                        },
+                    )?;
+                } else {
+                    // 가격 상승 중 마지막 array 끝에 닿으면 다음 array의 첫 tick을 확인할 수 없다
+                    require!(coin_in, ClmmError::NotEnoughTickArrays);
+                }
+                pool.tick_current = if coin_in { next_tick - 1 } else { next_tick };
+            } else {
+                pool.tick_current = math::tick_at_sqrt_price(pool.sqrt_price_x64)?;
+            }
+        }
+
+        let amount_in_used = amount_in - amount_remaining;
+        require!(
+            amount_out >= minimum_amount_out,
+            ClmmError::SlippageExceeded
+        );
+
+        let (source_vault, destination_vault) =
+            direction.orient(&ctx.accounts.coin_vault, &ctx.accounts.pc_vault);
+        let (user_source_account, user_destination_account) = direction.orient(
+            &ctx.accounts.user_coin_account,
+            &ctx.accounts.user_pc_account,
+        );
+        require!(
+            user_source_account.owner == ctx.accounts.user_authority.key(),
+            ClmmError::InvalidUserTokenOwner
+        );
+
+        // 사용자 → source_vault
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: user_source_account.to_account_info(),
+                    to: source_vault.to_account_info(),
+                    authority: ctx.accounts.user_authority.to_account_info(),
+                },
+            ),
+            amount_in_used,
+        )?;
+
+        // PDA signer (amm_authority)
+        let seeds: &[&[u8]] = &[
+            b"amm_authority",
+            pool_key.as_ref(),
+            &[ctx.bumps.amm_authority],
+        ];
+        let signer = &[seeds];
+
+        // pool → 사용자 (destination)
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: destination_vault.to_account_info(),
+                    to: user_destination_account.to_account_info(),
+                    authority: ctx.accounts.amm_authority.to_account_info(),
+                },
+                signer,
+            ),
+            amount_out,
+        )?;
+
+        msg!(
+            "Swapped {} for {} (tick {})",
+            amount_in_used,
+            amount_out,
+            pool.tick_current
+        );
+        Ok(())
+    }
+}
+
+/// 포지션 유동성 변경: 경계 tick, 구간 내부 fee growth, 포지션 수수료, 활성 유동성을 갱신하고
+/// 필요한 (coin, pc) 양을 돌려준다 (추가는 올림, 제거는 내림).
+fn modify_position<'info>(
+    pool: &mut PoolState,
+    pool_key: Pubkey,
+    position: &mut Position,
+    tick_array_lower: &AccountLoader<'info, TickArray>,
+    tick_array_upper: &AccountLoader<'info, TickArray>,
+    liquidity_delta: i128,
+) -> Result<(u64, u64)> {
+    let (tick_lower, tick_upper) = (position.tick_lower, position.tick_upper);
+    let spacing = pool.tick_spacing;
+    // 두 tick이 같은 array에 있으면 같은 계정을 두 번 넘긴다
+    let same_array = tick_array_lower.key() == tick_array_upper.key();
+
+    let mut lower_array = tick_array_lower.load_mut()?;
+    require_keys_eq!(lower_array.pool, pool_key, ClmmError::InvalidTickArray);
+    let mut lower = lower_array.get_tick(tick_lower, spacing)?;
+    let mut upper = if same_array {
+        lower_array.get_tick(tick_upper, spacing)?
+    } else {
+        let upper_array = tick_array_upper.load()?;
+        require_keys_eq!(upper_array.pool, pool_key, ClmmError::InvalidTickArray);
+        upper_array.get_tick(tick_upper, spacing)?
+    };
+
+    lower.update(tick_lower, pool, liquidity_delta, false)?;
+    upper.update(tick_upper, pool, liquidity_delta, true)?;
+
+    let (fee_growth_inside_coin, fee_growth_inside_pc) =
+        pool.fee_growth_inside(tick_lower, &lower, tick_upper, &upper);
+    position.update(
+        liquidity_delta,
+        fee_growth_inside_coin,
+        fee_growth_inside_pc,
+    )?;
+
+    // 더 이상 쓰이지 않는 tick은 비운다
+    if !lower.is_initialized() {
+        lower = TickState::default();
+    }
+    if !upper.is_initialized() {
+        upper = TickState::default();
+    }
+    lower_array.set_tick(tick_lower, spacing, lower)?;
+    if same_array {
+        lower_array.set_tick(tick_upper, spacing, upper)?;
+    } else {
+        tick_array_upper
+            .load_mut()?
+            .set_tick(tick_upper, spacing, upper)?;
+    }
+
+    // 현재가가 구간 안이면 활성 유동성도 바뀐다
+    if (tick_lower..tick_upper).contains(&pool.tick_current) {
+        pool.liquidity = math::add_liquidity_delta(pool.liquidity, liquidity_delta)?;
+    }
+
+    math::amounts_for_liquidity(
+        pool.sqrt_price_x64,
+        math::sqrt_price_at_tick(tick_lower)?,
+        math::sqrt_price_at_tick(tick_upper)?,
+        liquidity_delta.unsigned_abs(),
+        liquidity_delta > 0,
+    )
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapDirection {
+    CoinToPc,
+    PcToCoin,
+}
+
+impl SwapDirection {
+    /// (coin, pc) 쌍을 (source, destination) 순서로 바꾼다
+    pub fn orient<T>(self, coin: T, pc: T) -> (T, T) {
+        match self {
+            SwapDirection::CoinToPc => (coin, pc),
+            SwapDirection::PcToCoin => (pc, coin),
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(fee_bps: u64)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = PoolState::LEN,
+        seeds = [
+            b"pool_state",
+            coin_mint.key().as_ref(),
+            pc_mint.key().as_ref(),
+            &fee_bps.to_le_bytes()
+        ],
+        bump
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    /// CHECK: This is the PDA authority for the pool, verified via seeds.
+    #[account(seeds = [b"amm_authority", pool_state.key().as_ref()], bump)]
+    pub amm_authority: AccountInfo<'info>,
+
+    pub coin_mint: Account<'info, Mint>,
+    pub pc_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = coin_mint,
+        token::authority = amm_authority,
+        seeds = [b"coin_vault", pool_state.key().as_ref()],
+        bump
+    )]
+    pub coin_vault: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = payer,
+        token::mint = pc_mint,
+        token::authority = amm_authority,
+        seeds = [b"pc_vault", pool_state.key().as_ref()],
+        bump
+    )]
+    pub pc_vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(start_tick_index: i32)]
+pub struct InitializeTickArray<'info> {
+    pub pool_state: Account<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = TickArray::LEN,
+        seeds = [b"tick_array", pool_state.key().as_ref(), &start_tick_index.to_le_bytes()],
+        bump
+    )]
+    pub tick_array: AccountLoader<'info, TickArray>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tick_lower: i32, tick_upper: i32)]
+pub struct OpenPosition<'info> {
+    pub pool_state: Account<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Position::LEN,
+        seeds = [
+            b"position",
+            pool_state.key().as_ref(),
+            owner.key().as_ref(),
+            &tick_lower.to_le_bytes(),
+            &tick_upper.to_le_bytes()
+        ],
+        bump
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IncreaseLiquidity<'info> {
+    #[account(
+        mut,
+        has_one = coin_vault @ ClmmError::InvalidCoinVault,
+        has_one = pc_vault @ ClmmError::InvalidPcVault
+    )]
+    pub pool_state: Account<'info, PoolState>,
+
+    #[account(
+        mut,
+        has_one = owner @ ClmmError::Unauthorized,
+        constraint = position.pool == pool_state.key() @ ClmmError::InvalidPosition
+    )]
+    pub position: Account<'info, Position>,
+
+    // lower/upper tick이 같은 array에 있으면 같은 계정
+    #[account(mut)]
+    pub tick_array_lower: AccountLoader<'info, TickArray>,
+    #[account(mut)]
+    pub tick_array_upper: AccountLoader<'info, TickArray>,
+
+    #[account(mut)]
+    pub coin_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pc_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_coin_account.mint == pool_state.coin_mint @ ClmmError::InvalidUserTokenMint,
+        constraint = user_coin_account.owner == owner.key() @ ClmmError::InvalidUserTokenOwner
+    )]
+    pub user_coin_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_pc_account.mint == pool_state.pc_mint @ ClmmError::InvalidUserTokenMint,
+        constraint = user_pc_account.owner == owner.key() @ ClmmError::InvalidUserTokenOwner
+    )]
+    pub user_pc_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DecreaseLiquidity<'info> {
+    #[account(
+        mut,
+        has_one = coin_vault @ ClmmError::InvalidCoinVault,
+        has_one = pc_vault @ ClmmError::InvalidPcVault
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    /// CHECK: This is the PDA authority for the pool, verified via seeds.
+    #[account(
+        seeds = [b"amm_authority", pool_state.key().as_ref()],
+        bump
+    )]
+    pub amm_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        has_one = owner @ ClmmError::Unauthorized,
+        constraint = position.pool == pool_state.key() @ ClmmError::InvalidPosition
+    )]
+    pub position: Account<'info, Position>,
+
+    // lower/upper tick이 같은 array에 있으면 같은 계정
+    #[account(mut)]
+    pub tick_array_lower: AccountLoader<'info, TickArray>,
+    #[account(mut)]
+    pub tick_array_upper: AccountLoader<'info, TickArray>,
+
+    #[account(mut)]
+    pub coin_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pc_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_coin_account.mint == pool_state.coin_mint @ ClmmError::InvalidUserTokenMint
+    )]
+    pub user_coin_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_pc_account.mint == pool_state.pc_mint @ ClmmError::InvalidUserTokenMint
+    )]
+    pub user_pc_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePosition<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ ClmmError::Unauthorized,
+        constraint = position.liquidity == 0
+            && position.tokens_owed_coin == 0
+            && position.tokens_owed_pc == 0 @ ClmmError::PositionNotEmpty
+    )]
+    pub position: Account<'info, Position>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        mut,
+        has_one = coin_vault @ ClmmError::InvalidCoinVault,
+        has_one = pc_vault @ ClmmError::InvalidPcVault
+    )]
+    pub pool_state: Account<'info, PoolState>,
+    /// CHECK: This is the PDA authority for the pool, verified via seeds.
+    #[account(
+        seeds = [b"amm_authority", pool_state.key().as_ref()],
+        bump
+    )]
+    pub amm_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub coin_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub pc_vault: Account<'info, TokenAccount>,
+
+    // 지불 계정의 owner는 방향이 정해진 뒤 instruction에서 검사
+    #[account(
+        mut,
+        constraint = user_coin_account.mint == pool_state.coin_mint @ ClmmError::InvalidUserTokenMint
+    )]
+    pub user_coin_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_pc_account.mint == pool_state.pc_mint @ ClmmError::InvalidUserTokenMint
+    )]
+    pub user_pc_account: Account<'info, TokenAccount>,
+
+    pub user_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[error_code]
+pub enum ClmmError {
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Slippage tolerance exceeded")]
+    SlippageExceeded,
+    #[msg("Invalid fee tier")]
+    InvalidFeeTier,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Coin mint must sort before pc mint")]
+    MintsNotSorted,
+    #[msg("Tick is out of range or not aligned to the tick spacing")]
+    InvalidTick,
+    #[msg("Lower tick must be below upper tick")]
+    InvalidTickRange,
+    #[msg("Tick array does not match the pool or tick")]
+    InvalidTickArray,
+    #[msg("Not enough tick arrays for this swap")]
+    NotEnoughTickArrays,
+    #[msg("Sqrt price is out of range")]
+    InvalidSqrtPrice,
+    #[msg("Invalid sqrt price limit")]
+    InvalidPriceLimit,
+    #[msg("Position does not belong to this pool")]
+    InvalidPosition,
+    #[msg("Position still holds liquidity or fees")]
+    PositionNotEmpty,
+    #[msg("Signer is not the position owner")]
+    Unauthorized,
+    #[msg("Coin vault does not match the pool")]
+    InvalidCoinVault,
+    #[msg("Pc vault does not match the pool")]
+    InvalidPcVault,
+    #[msg("User token account has the wrong mint")]
+    InvalidUserTokenMint,
+    #[msg("User token account is not owned by the signer")]
+    InvalidUserTokenOwner,
+    #[msg("Transaction deadline exceeded")]
+    DeadlineExceeded,
+}
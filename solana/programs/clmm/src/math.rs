@@ -0,0 +1,448 @@
+//! Q64.64 sqrt price 수학 (Uniswap v3 방식).
+//!
+//! 가격 p = pc / coin, tick i 의 sqrt price = sqrt(1.0001)^i * 2^64.
+//! 수량 계산은 항상 풀에 유리하게 반올림한다 (받는 양은 올림, 내주는 양은 내림).
+
+use crate::ClmmError;
+use anchor_lang::prelude::*;
+
+// 256비트 정수 — Q64.64 곱셈 중간값용
+#[allow(clippy::all)]
+mod u256 {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+pub use u256::U256;
+
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+/// sqrt_price_at_tick(MIN_TICK), sqrt_price_at_tick(MAX_TICK)
+pub const MIN_SQRT_PRICE_X64: u128 = 4_295_048_017;
+pub const MAX_SQRT_PRICE_X64: u128 = 79_226_673_515_401_279_992_447_579_062;
+
+// sqrt(1.0001)^(-2^i) (Q128), i = 0..19 — |tick| <= MAX_TICK < 2^19
+const TICK_RATIOS_X128: [u128; 19] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e2139,
+    0xfff2e50f5f656932ef12357cf3c7fdcb,
+    0xffe5caca7e10e4e61c3624eaa0941ccf,
+    0xffcb9843d60f6159c9db58835c926643,
+    0xff973b41fa98c081472e6896dfb254bf,
+    0xff2ea16466c96a3843ec78b326b52860,
+    0xfe5dee046a99a2a811c461f1969c3052,
+    0xfcbe86c7900a88aedcffc83b479aa3a3,
+    0xf987a7253ac413176f2b074cf7815e53,
+    0xf3392b0822b70005940c7a398e4b70f2,
+    0xe7159475a2c29b7443b29c7fa6e889d8,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e4,
+    0x70d869a156d2a1b890bb3df62baf32f6,
+    0x31be135f97d08fd981231505542fcfa5,
+    0x9aa508b5b7a84e1c677de54f3e99bc8,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe97,
+];
+
+/// tick의 sqrt price (Q64.64, 올림)
+pub fn sqrt_price_at_tick(tick: i32) -> Result<u128> {
+    require!(
+        (MIN_TICK..=MAX_TICK).contains(&tick),
+        ClmmError::InvalidTick
+    );
+
+    let abs_tick = tick.unsigned_abs();
+    let mut ratio = U256::one() << 128;
+    for (i, factor) in TICK_RATIOS_X128.iter().enumerate() {
+        if abs_tick & (1 << i) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+    // Q128 → Q64.64
+    Ok(((ratio + U256::from(u64::MAX)) >> 64).as_u128())
+}
+
+/// sqrt_price_at_tick(tick) <= sqrt_price 인 가장 큰 tick (이분 탐색)
+pub fn tick_at_sqrt_price(sqrt_price_x64: u128) -> Result<i32> {
+    require!(
+        (MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64).contains(&sqrt_price_x64),
+        ClmmError::InvalidSqrtPrice
+    );
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price_x64 {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+fn mul_div(a: U256, b: U256, denominator: U256, round_up: bool) -> Result<U256> {
+    let product = a.checked_mul(b).ok_or(ClmmError::MathOverflow)?;
+    let quotient = product / denominator;
+    if round_up && !(product % denominator).is_zero() {
+        Ok(quotient + 1)
+    } else {
+        Ok(quotient)
+    }
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    require!(value <= U256::from(u128::MAX), ClmmError::MathOverflow);
+    Ok(value.as_u128())
+}
+
+fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(ClmmError::MathOverflow))
+}
+
+/// 가격 구간 [a, b]에서 유동성 L에 해당하는 coin 양: L * (b - a) / (a * b)
+pub fn coin_amount_delta(
+    sqrt_price_a_x64: u128,
+    sqrt_price_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128> {
+    let (lower, upper) = if sqrt_price_a_x64 <= sqrt_price_b_x64 {
+        (sqrt_price_a_x64, sqrt_price_b_x64)
+    } else {
+        (sqrt_price_b_x64, sqrt_price_a_x64)
+    };
+    require!(lower > 0, ClmmError::InvalidSqrtPrice);
+
+    let numerator = U256::from(liquidity) << 64;
+    let amount = mul_div(
+        numerator,
+        U256::from(upper - lower),
+        U256::from(upper),
+        round_up,
+    )?;
+    let lower = U256::from(lower);
+    let amount = if round_up {
+        (amount + lower - 1) / lower
+    } else {
+        amount / lower
+    };
+    to_u128(amount)
+}
+
+/// 가격 구간 [a, b]에서 유동성 L에 해당하는 pc 양: L * (b - a)
+pub fn pc_amount_delta(
+    sqrt_price_a_x64: u128,
+    sqrt_price_b_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<u128> {
+    let difference = sqrt_price_a_x64.abs_diff(sqrt_price_b_x64);
+    to_u128(mul_div(
+        U256::from(liquidity),
+        U256::from(difference),
+        U256::one() << 64,
+        round_up,
+    )?)
+}
+
+/// `amount_in`을 넣은 뒤의 sqrt price. coin 입력이면 가격이 내려가고 (올림),
+/// pc 입력이면 올라간다 (내림).
+pub fn next_sqrt_price_from_input(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    amount_in: u64,
+    coin_in: bool,
+) -> Result<u128> {
+    require!(
+        sqrt_price_x64 > 0 && liquidity > 0,
+        ClmmError::InvalidSqrtPrice
+    );
+    if amount_in == 0 {
+        return Ok(sqrt_price_x64);
+    }
+
+    if coin_in {
+        // L * sqrt / (L + amount * sqrt)
+        let numerator = U256::from(liquidity) << 64;
+        let sqrt_price = U256::from(sqrt_price_x64);
+        let denominator = numerator + U256::from(amount_in) * sqrt_price;
+        match mul_div(numerator, sqrt_price, denominator, true) {
+            Ok(price) => to_u128(price),
+            // 곱이 넘치면 L / (L / sqrt + amount) 형태로 (정밀도는 조금 낮지만 여전히 올림)
+            Err(_) => {
+                let denominator = numerator / sqrt_price + U256::from(amount_in);
+                to_u128((numerator + denominator - 1) / denominator)
+            }
+        }
+    } else {
+        // sqrt + amount / L
+        let quotient = to_u128((U256::from(amount_in) << 64) / U256::from(liquidity))?;
+        sqrt_price_x64
+            .checked_add(quotient)
+            .ok_or(error!(ClmmError::MathOverflow))
+    }
+}
+
+/// 유동성 L을 [lower, upper] 구간에 넣거나 뺄 때 필요한 (coin, pc) 양
+pub fn amounts_for_liquidity(
+    sqrt_price_x64: u128,
+    sqrt_price_lower_x64: u128,
+    sqrt_price_upper_x64: u128,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u64, u64)> {
+    let (coin, pc) = if sqrt_price_x64 <= sqrt_price_lower_x64 {
+        // 현재가가 구간 아래: 전부 coin
+        (
+            coin_amount_delta(
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                liquidity,
+                round_up,
+            )?,
+            0,
+        )
+    } else if sqrt_price_x64 < sqrt_price_upper_x64 {
+        (
+            coin_amount_delta(sqrt_price_x64, sqrt_price_upper_x64, liquidity, round_up)?,
+            pc_amount_delta(sqrt_price_lower_x64, sqrt_price_x64, liquidity, round_up)?,
+        )
+    } else {
+        // 현재가가 구간 위: 전부 pc
+        (
+            0,
+            pc_amount_delta(
+                sqrt_price_lower_x64,
+                sqrt_price_upper_x64,
+                liquidity,
+                round_up,
+            )?,
+        )
+    };
+    Ok((to_u64(coin)?, to_u64(pc)?))
+}
+
+/// 한 tick 구간 안에서의 스왑 결과
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price_next_x64: u128,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+}
+
+/// 현재가에서 `sqrt_price_target_x64` 방향으로 최대 `amount_remaining`(수수료 포함)을 넣는다.
+pub fn compute_swap_step(
+    sqrt_price_current_x64: u128,
+    sqrt_price_target_x64: u128,
+    liquidity: u128,
+    amount_remaining: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> Result<SwapStep> {
+    require!(fee_numerator < fee_denominator, ClmmError::InvalidFeeTier);
+    let coin_in = sqrt_price_current_x64 >= sqrt_price_target_x64;
+
+    let amount_remaining_less_fee = (amount_remaining as u128
+        * (fee_denominator - fee_numerator) as u128
+        / fee_denominator as u128) as u64;
+    let amount_in_to_target = if coin_in {
+        coin_amount_delta(
+            sqrt_price_target_x64,
+            sqrt_price_current_x64,
+            liquidity,
+            true,
+        )?
+    } else {
+        pc_amount_delta(
+            sqrt_price_current_x64,
+            sqrt_price_target_x64,
+            liquidity,
+            true,
+        )?
+    };
+
+    let reached_target = amount_remaining_less_fee as u128 >= amount_in_to_target;
+    let sqrt_price_next_x64 = if reached_target {
+        sqrt_price_target_x64
+    } else {
+        next_sqrt_price_from_input(
+            sqrt_price_current_x64,
+            liquidity,
+            amount_remaining_less_fee,
+            coin_in,
+        )?
+    };
+
+    let (amount_in, amount_out) = if coin_in {
+        (
+            coin_amount_delta(sqrt_price_next_x64, sqrt_price_current_x64, liquidity, true)?,
+            pc_amount_delta(
+                sqrt_price_next_x64,
+                sqrt_price_current_x64,
+                liquidity,
+                false,
+            )?,
+        )
+    } else {
+        (
+            pc_amount_delta(sqrt_price_current_x64, sqrt_price_next_x64, liquidity, true)?,
+            coin_amount_delta(
+                sqrt_price_current_x64,
+                sqrt_price_next_x64,
+                liquidity,
+                false,
+            )?,
+        )
+    };
+    let amount_in = to_u64(amount_in)?;
+
+    // 목표가에 못 미쳤으면 남은 입력 전부가 수수료
+    let fee_amount = if reached_target {
+        to_u64(
+            (amount_in as u128 * fee_numerator as u128)
+                .div_ceil((fee_denominator - fee_numerator) as u128),
+        )?
+    } else {
+        amount_remaining - amount_in
+    };
+
+    Ok(SwapStep {
+        sqrt_price_next_x64,
+        amount_in,
+        amount_out: to_u64(amount_out)?,
+        fee_amount,
+    })
+}
+
+/// 유동성에 부호 있는 변화량 적용
+pub fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128> {
+    let result = if delta >= 0 {
+        liquidity.checked_add(delta as u128)
+    } else {
+        liquidity.checked_sub(delta.unsigned_abs())
+    };
+    result.ok_or(error!(ClmmError::MathOverflow))
+}
+
+/// 유동성 단위당 누적 수수료 (Q64.64) 증가분
+pub fn fee_growth_delta(fee_amount: u64, liquidity: u128) -> u128 {
+    if liquidity == 0 {
+        return 0;
+    }
+    ((fee_amount as u128) << 64) / liquidity
+}
+
+/// fee growth 차이 × 유동성 → 토큰 수량 (내림)
+pub fn fees_owed(fee_growth_delta_x64: u128, liquidity: u128) -> Result<u64> {
+    let owed = (U256::from(fee_growth_delta_x64) * U256::from(liquidity)) >> 64;
+    to_u64(to_u128(owed)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q64: u128 = 1 << 64;
+
+    #[test]
+    fn sqrt_price_bounds() {
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), Q64);
+        assert_eq!(sqrt_price_at_tick(MIN_TICK).unwrap(), MIN_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK).unwrap(), MAX_SQRT_PRICE_X64);
+        assert!(sqrt_price_at_tick(MIN_TICK - 1).is_err());
+        assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+    }
+
+    #[test]
+    fn sqrt_price_matches_one_basis_point_steps() {
+        // sqrt(1.0001) * 2^64, 1/sqrt(1.0001) * 2^64
+        assert_eq!(sqrt_price_at_tick(1).unwrap(), 18_447_666_387_855_959_851);
+        assert_eq!(sqrt_price_at_tick(-1).unwrap(), 18_445_821_805_675_392_312);
+    }
+
+    #[test]
+    fn sqrt_price_is_monotonic() {
+        let mut previous = 0;
+        for tick in (MIN_TICK..=MAX_TICK).step_by(997) {
+            let price = sqrt_price_at_tick(tick).unwrap();
+            assert!(price > previous);
+            previous = price;
+        }
+    }
+
+    #[test]
+    fn tick_at_sqrt_price_round_trips() {
+        for tick in [MIN_TICK, -200_000, -60, -1, 0, 1, 60, 123_456, MAX_TICK - 1] {
+            let price = sqrt_price_at_tick(tick).unwrap();
+            assert_eq!(tick_at_sqrt_price(price).unwrap(), tick);
+            assert_eq!(tick_at_sqrt_price(price + 1).unwrap(), tick);
+            if tick > MIN_TICK {
+                assert_eq!(tick_at_sqrt_price(price - 1).unwrap(), tick - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn amounts_round_in_favor_of_the_pool() {
+        let lower = sqrt_price_at_tick(-600).unwrap();
+        let upper = sqrt_price_at_tick(600).unwrap();
+        let (coin_up, pc_up) = amounts_for_liquidity(Q64, lower, upper, 1_000_000, true).unwrap();
+        let (coin_down, pc_down) =
+            amounts_for_liquidity(Q64, lower, upper, 1_000_000, false).unwrap();
+        assert!(coin_up >= coin_down && pc_up >= pc_down);
+        assert!(coin_up - coin_down <= 1 && pc_up - pc_down <= 1);
+        // 가격 1에서 대칭 구간이면 양쪽 수량이 (거의) 같다
+        assert!(coin_up.abs_diff(pc_up) <= 1);
+
+        // 구간 밖은 한쪽 토큰만
+        assert_eq!(
+            amounts_for_liquidity(lower - 1, lower, upper, 1_000_000, true)
+                .unwrap()
+                .1,
+            0
+        );
+        assert_eq!(
+            amounts_for_liquidity(upper, lower, upper, 1_000_000, true)
+                .unwrap()
+                .0,
+            0
+        );
+    }
+
+    #[test]
+    fn swap_step_stops_at_target_or_exhausts_input() {
+        let liquidity = 1_000_000_000u128;
+        let target = sqrt_price_at_tick(-60).unwrap();
+
+        // 작은 입력: 목표가 전에 멈추고 입력을 모두 소비
+        let step = compute_swap_step(Q64, target, liquidity, 1_000, 30, 10_000).unwrap();
+        assert!(step.sqrt_price_next_x64 > target && step.sqrt_price_next_x64 < Q64);
+        assert_eq!(step.amount_in + step.fee_amount, 1_000);
+        assert!(step.amount_out < step.amount_in);
+
+        // 큰 입력: 목표가에서 멈추고 나머지는 남긴다
+        let step = compute_swap_step(Q64, target, liquidity, u32::MAX as u64, 30, 10_000).unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+        assert!(step.amount_in + step.fee_amount < u32::MAX as u64);
+
+        // pc 입력은 가격을 올린다
+        let target = sqrt_price_at_tick(60).unwrap();
+        let step = compute_swap_step(Q64, target, liquidity, 1_000, 30, 10_000).unwrap();
+        assert!(step.sqrt_price_next_x64 > Q64 && step.sqrt_price_next_x64 < target);
+    }
+
+    #[test]
+    fn swap_step_without_liquidity_jumps_to_target() {
+        let target = sqrt_price_at_tick(-60).unwrap();
+        let step = compute_swap_step(Q64, target, 0, 1_000, 30, 10_000).unwrap();
+        assert_eq!(step.sqrt_price_next_x64, target);
+        assert_eq!(
+            (step.amount_in, step.amount_out, step.fee_amount),
+            (0, 0, 0)
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use crate::math::{self, MAX_TICK, MIN_TICK};
+use crate::ClmmError;
+use anchor_lang::prelude::*;
+
+/// tick array 하나가 담는 초기화 가능 tick 수
+pub const TICK_ARRAY_SIZE: i32 = 60;
+
+#[account]
+pub struct PoolState {
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+
+    pub tick_spacing: u16,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+
+    // 현재 가격과 활성 유동성 (tick_current가 속한 구간의 유동성 합)
+    pub sqrt_price_x64: u128,
+    pub tick_current: i32,
+    pub liquidity: u128,
+
+    // 유동성 1 단위당 누적 수수료 (Q64.64, wrapping)
+    pub fee_growth_global_coin_x64: u128,
+    pub fee_growth_global_pc_x64: u128,
+}
+
+impl PoolState {
+    // 계산: discriminator(8) + pubkey 4*32 + u16 2 + u64 2*8 + u128 4*16 + i32 4
+    pub const LEN: usize = 8 + 4 * 32 + 2 + 2 * 8 + 4 * 16 + 4; // = 222
+
+    /// tick array 하나가 덮는 tick 범위
+    pub fn tick_array_span(&self) -> i32 {
+        self.tick_spacing as i32 * TICK_ARRAY_SIZE
+    }
+
+    /// `tick`을 포함하는 tick array의 시작 tick
+    pub fn tick_array_start_index(&self, tick: i32) -> i32 {
+        tick.div_euclid(self.tick_array_span()) * self.tick_array_span()
+    }
+
+    /// 포지션 경계로 쓸 수 있는 tick인지 (범위 + spacing 정렬)
+    pub fn check_tick(&self, tick: i32) -> Result<()> {
+        require!(
+            (MIN_TICK..=MAX_TICK).contains(&tick) && tick % self.tick_spacing as i32 == 0,
+            ClmmError::InvalidTick
+        );
+        Ok(())
+    }
+
+    /// [tick_lower, tick_upper) 밖에서 쌓인 수수료를 제외한 구간 내부 fee growth (coin, pc)
+    pub fn fee_growth_inside(
+        &self,
+        tick_lower: i32,
+        lower: &TickState,
+        tick_upper: i32,
+        upper: &TickState,
+    ) -> (u128, u128) {
+        let inside = |global: u128, lower_outside: u128, upper_outside: u128| {
+            let below = if self.tick_current >= tick_lower {
+                lower_outside
+            } else {
+                global.wrapping_sub(lower_outside)
+            };
+            let above = if self.tick_current < tick_upper {
+                upper_outside
+            } else {
+                global.wrapping_sub(upper_outside)
+            };
+            global.wrapping_sub(below).wrapping_sub(above)
+        };
+        (
+            inside(
+                self.fee_growth_global_coin_x64,
+                lower.fee_growth_outside_coin_x64,
+                upper.fee_growth_outside_coin_x64,
+            ),
+            inside(
+                self.fee_growth_global_pc_x64,
+                lower.fee_growth_outside_pc_x64,
+                upper.fee_growth_outside_pc_x64,
+            ),
+        )
+    }
+}
+
+#[zero_copy(unsafe)]
+#[repr(C, packed)]
+#[derive(Default)]
+pub struct TickState {
+    // 이 tick을 왼→오로 건널 때 활성 유동성 변화량
+    pub liquidity_net: i128,
+    // 이 tick을 경계로 쓰는 유동성 총합 (0이면 미초기화)
+    pub liquidity_gross: u128,
+    // tick 반대편(현재가 기준)에서 쌓인 fee growth
+    pub fee_growth_outside_coin_x64: u128,
+    pub fee_growth_outside_pc_x64: u128,
+}
+
+impl TickState {
+    pub fn is_initialized(&self) -> bool {
+        self.liquidity_gross != 0
+    }
+
+    /// 포지션 유동성 변경을 반영한다. 처음 초기화될 때 현재가 아래 tick은 지금까지의
+    /// fee growth 전부가 '바깥'에서 쌓인 것으로 간주한다 (Uniswap v3 관례).
+    pub fn update(
+        &mut self,
+        tick: i32,
+        pool: &PoolState,
+        liquidity_delta: i128,
+        upper: bool,
+    ) -> Result<()> {
+        let liquidity_gross = math::add_liquidity_delta(self.liquidity_gross, liquidity_delta)?;
+        if !self.is_initialized() && tick <= pool.tick_current {
+            self.fee_growth_outside_coin_x64 = pool.fee_growth_global_coin_x64;
+            self.fee_growth_outside_pc_x64 = pool.fee_growth_global_pc_x64;
+        }
+        self.liquidity_gross = liquidity_gross;
+        let liquidity_net = if upper {
+            self.liquidity_net.checked_sub(liquidity_delta)
+        } else {
+            self.liquidity_net.checked_add(liquidity_delta)
+        };
+        self.liquidity_net = liquidity_net.ok_or(ClmmError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// 스왑이 이 tick을 건널 때 바깥 fee growth를 뒤집는다. 반환값은 liquidity_net.
+    pub fn cross(
+        &mut self,
+        fee_growth_global_coin_x64: u128,
+        fee_growth_global_pc_x64: u128,
+    ) -> i128 {
+        self.fee_growth_outside_coin_x64 =
+            fee_growth_global_coin_x64.wrapping_sub(self.fee_growth_outside_coin_x64);
+        self.fee_growth_outside_pc_x64 =
+            fee_growth_global_pc_x64.wrapping_sub(self.fee_growth_outside_pc_x64);
+        self.liquidity_net
+    }
+}
+
+/// 연속된 TICK_ARRAY_SIZE 개의 tick (spacing 간격). zero-copy — 스택에 올리지 않는다.
+#[account(zero_copy(unsafe))]
+#[repr(C, packed)]
+pub struct TickArray {
+    pub pool: Pubkey,
+    pub start_tick_index: i32,
+    pub ticks: [TickState; TICK_ARRAY_SIZE as usize],
+}
+
+impl TickArray {
+    pub const LEN: usize = 8 + 32 + 4 + TICK_ARRAY_SIZE as usize * 64; // = 3884
+
+    fn offset(&self, tick: i32, tick_spacing: u16) -> Result<usize> {
+        let start = self.start_tick_index;
+        let offset = (tick - start) / tick_spacing as i32;
+        require!(
+            tick >= start && tick % tick_spacing as i32 == 0 && offset < TICK_ARRAY_SIZE,
+            ClmmError::InvalidTickArray
+        );
+        Ok(offset as usize)
+    }
+
+    pub fn get_tick(&self, tick: i32, tick_spacing: u16) -> Result<TickState> {
+        Ok(self.ticks[self.offset(tick, tick_spacing)?])
+    }
+
+    pub fn set_tick(&mut self, tick: i32, tick_spacing: u16, state: TickState) -> Result<()> {
+        let offset = self.offset(tick, tick_spacing)?;
+        self.ticks[offset] = state;
+        Ok(())
+    }
+
+    /// 이 array 안에서 스왑 방향으로 다음 초기화된 tick.
+    /// coin 입력(가격 하락)이면 `tick_current` 이하 중 가장 큰 것, 아니면 초과 중 가장 작은 것.
+    pub fn next_initialized_tick(
+        &self,
+        tick_current: i32,
+        tick_spacing: u16,
+        coin_in: bool,
+    ) -> Option<i32> {
+        let start = self.start_tick_index;
+        let spacing = tick_spacing as i32;
+        let current_offset = (tick_current - start).div_euclid(spacing);
+        let initialized = |offset: i32| self.ticks[offset as usize].is_initialized();
+
+        let found = if coin_in {
+            if current_offset < 0 {
+                return None;
+            }
+            (0..=current_offset.min(TICK_ARRAY_SIZE - 1))
+                .rev()
+                .find(|&offset| initialized(offset))
+        } else {
+            ((current_offset + 1).max(0)..TICK_ARRAY_SIZE).find(|&offset| initialized(offset))
+        };
+        found.map(|offset| start + offset * spacing)
+    }
+}
+
+#[account]
+pub struct Position {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    // 마지막 갱신 시점의 구간 내부 fee growth
+    pub fee_growth_inside_coin_last_x64: u128,
+    pub fee_growth_inside_pc_last_x64: u128,
+    // 적립되었지만 아직 수령하지 않은 수수료
+    pub tokens_owed_coin: u64,
+    pub tokens_owed_pc: u64,
+}
+
+impl Position {
+    // 계산: discriminator(8) + pubkey 2*32 + i32 2*4 + u128 3*16 + u64 2*8
+    pub const LEN: usize = 8 + 2 * 32 + 2 * 4 + 3 * 16 + 2 * 8; // = 144
+
+    /// 구간 내부 fee growth 변화분만큼 수수료를 적립하고 유동성을 바꾼다.
+    pub fn update(
+        &mut self,
+        liquidity_delta: i128,
+        fee_growth_inside_coin_x64: u128,
+        fee_growth_inside_pc_x64: u128,
+    ) -> Result<()> {
+        let owed_coin = math::fees_owed(
+            fee_growth_inside_coin_x64.wrapping_sub(self.fee_growth_inside_coin_last_x64),
+            self.liquidity,
+        )?;
+        let owed_pc = math::fees_owed(
+            fee_growth_inside_pc_x64.wrapping_sub(self.fee_growth_inside_pc_last_x64),
+            self.liquidity,
+        )?;
+
+        self.liquidity = math::add_liquidity_delta(self.liquidity, liquidity_delta)?;
+        self.fee_growth_inside_coin_last_x64 = fee_growth_inside_coin_x64;
+        self.fee_growth_inside_pc_last_x64 = fee_growth_inside_pc_x64;
+        self.tokens_owed_coin = self
+            .tokens_owed_coin
+            .checked_add(owed_coin)
+            .ok_or(ClmmError::MathOverflow)?;
+        self.tokens_owed_pc = self
+            .tokens_owed_pc
+            .checked_add(owed_pc)
+            .ok_or(ClmmError::MathOverflow)?;
+        Ok(())
+    }
+}
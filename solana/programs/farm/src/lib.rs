@@ -0,0 +1,465 @@
+use ammv2::AmmInfo;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+pub mod state;
+
+pub use state::*;
+
+declare_id!("Fn8NreXCXd5nroEMiwzocu8XLejafcneNJQnJ4Ay8j5z");
+
+/// ammv2 LP 토큰 스테이킹 farm.
+///
+/// farm은 LP mint당 하나 ([b"farm", lp_mint])이고, 스테이크된 LP와 보상 토큰은 farm PDA가
+/// authority인 vault에 보관된다. 보상은 토큰별 초당 배출량을 reward-per-share 누적값으로
+/// 배분하며, stake/unstake/claim은 먼저 farm을 갱신하고 사용자 몫을 정산한다.
+#[program]
+pub mod farm {
+    use super::*;
+
+    /// LP mint에 대한 farm 생성. 풀 admin만 만들 수 있고 그대로 farm admin이 된다.
+    pub fn initialize_farm(ctx: Context<InitializeFarm>) -> Result<()> {
+        let farm = &mut ctx.accounts.farm;
+        farm.amm_info = ctx.accounts.amm_info.key();
+        farm.lp_mint = ctx.accounts.lp_mint.key();
+        farm.lp_vault = ctx.accounts.lp_vault.key();
+        farm.admin = ctx.accounts.admin.key();
+        farm.bump = ctx.bumps.farm;
+        farm.total_staked = 0;
+        farm.last_update_timestamp = Clock::get()?.unix_timestamp;
+        farm.reward_count = 0;
+
+        msg!("Farm initialized for LP mint {}", farm.lp_mint);
+        Ok(())
+    }
+
+    /// 보상 토큰 추가. 배출은 `fund`로 토큰을 넣은 뒤부터 시작된다.
+    pub fn add_reward(ctx: Context<AddReward>, emission_per_second: u64) -> Result<()> {
+        let farm = &mut ctx.accounts.farm;
+        require!(
+            (farm.reward_count as usize) < MAX_REWARDS,
+            FarmError::TooManyRewards
+        );
+
+        let index = farm.reward_count as usize;
+        farm.rewards[index] = RewardInfo {
+            reward_mint: ctx.accounts.reward_mint.key(),
+            reward_vault: ctx.accounts.reward_vault.key(),
+            emission_per_second,
+            remaining: 0,
+            reward_per_share_x64: 0,
+        };
+        farm.reward_count += 1;
+
+        msg!(
+            "Reward {} added: {} per second",
+            ctx.accounts.reward_mint.key(),
+            emission_per_second
+        );
+        Ok(())
+    }
+
+    /// 보상 vault에 `amount` 입금 (admin). transfer fee가 있는 mint는 vault에 실제로 도착한 양만
+    /// 보상으로 적립된다.
+    pub fn fund(ctx: Context<Fund>, reward_index: u8, amount: u64) -> Result<()> {
+        require!(amount > 0, FarmError::InvalidAmount);
+        let farm = &mut ctx.accounts.farm;
+        farm.reward(reward_index, ctx.accounts.reward_vault.key())?;
+        farm.update(Clock::get()?.unix_timestamp)?;
+
+        let before = ctx.accounts.reward_vault.amount;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.admin_reward_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.admin.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+        ctx.accounts.reward_vault.reload()?;
+        let received = ctx
+            .accounts
+            .reward_vault
+            .amount
+            .checked_sub(before)
+            .ok_or(FarmError::MathOverflow)?;
+
+        let reward = &mut farm.rewards[reward_index as usize];
+        reward.remaining = reward
+            .remaining
+            .checked_add(received)
+            .ok_or(FarmError::MathOverflow)?;
+
+        msg!("Reward {} funded with {}", reward_index, received);
+        Ok(())
+    }
+
+    /// 보상 배출량 변경 (admin). 변경 전까지의 배출은 이전 속도로 반영된다.
+    pub fn set_rate(
+        ctx: Context<SetRate>,
+        reward_index: u8,
+        emission_per_second: u64,
+    ) -> Result<()> {
+        let farm = &mut ctx.accounts.farm;
+        require!(
+            reward_index < farm.reward_count,
+            FarmError::InvalidRewardIndex
+        );
+        farm.update(Clock::get()?.unix_timestamp)?;
+        farm.rewards[reward_index as usize].emission_per_second = emission_per_second;
+
+        msg!(
+            "Reward {} rate set to {} per second",
+            reward_index,
+            emission_per_second
+        );
+        Ok(())
+    }
+
+    /// 사용자 스테이크 계정 생성
+    pub fn create_user_stake(ctx: Context<CreateUserStake>) -> Result<()> {
+        let user_stake = &mut ctx.accounts.user_stake;
+        user_stake.farm = ctx.accounts.farm.key();
+        user_stake.owner = ctx.accounts.owner.key();
+        user_stake.amount = 0;
+        // 지금까지의 누적분은 이 사용자 몫이 아니다
+        for (paid, reward) in user_stake
+            .reward_per_share_paid_x64
+            .iter_mut()
+            .zip(ctx.accounts.farm.rewards.iter())
+        {
+            *paid = reward.reward_per_share_x64;
+        }
+        user_stake.rewards_owed = [0; MAX_REWARDS];
+
+        msg!("User stake created for {}", user_stake.owner);
+        Ok(())
+    }
+
+    /// LP `amount` 스테이크
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require!(amount > 0, FarmError::InvalidAmount);
+        let farm = &mut ctx.accounts.farm;
+        let user_stake = &mut ctx.accounts.user_stake;
+        farm.update(Clock::get()?.unix_timestamp)?;
+        user_stake.settle(farm)?;
+
+        // 사용자 → LP vault
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.user_lp_account.to_account_info(),
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.lp_vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.lp_mint.decimals,
+        )?;
+
+        user_stake.amount = user_stake
+            .amount
+            .checked_add(amount)
+            .ok_or(FarmError::MathOverflow)?;
+        farm.total_staked = farm
+            .total_staked
+            .checked_add(amount)
+            .ok_or(FarmError::MathOverflow)?;
+
+        msg!("Staked {} LP (total {})", amount, farm.total_staked);
+        Ok(())
+    }
+
+    /// LP `amount` 인출. 쌓인 보상은 rewards_owed에 남고 `claim`으로 받는다.
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        require!(amount > 0, FarmError::InvalidAmount);
+        let farm = &mut ctx.accounts.farm;
+        let user_stake = &mut ctx.accounts.user_stake;
+        require!(amount <= user_stake.amount, FarmError::InsufficientStake);
+        farm.update(Clock::get()?.unix_timestamp)?;
+        user_stake.settle(farm)?;
+
+        user_stake.amount -= amount;
+        farm.total_staked -= amount;
+
+        // PDA signer (farm)
+        let seeds: &[&[u8]] = &[b"farm", farm.lp_mint.as_ref(), &[farm.bump]];
+        let signer = &[seeds];
+
+        // LP vault → 사용자
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.lp_vault.to_account_info(),
+                    mint: ctx.accounts.lp_mint.to_account_info(),
+                    to: ctx.accounts.user_lp_account.to_account_info(),
+                    authority: farm.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+            ctx.accounts.lp_mint.decimals,
+        )?;
+
+        msg!("Unstaked {} LP (total {})", amount, farm.total_staked);
+        Ok(())
+    }
+
+    /// 보상 `reward_index`의 쌓인 몫을 모두 수령
+    pub fn claim(ctx: Context<Claim>, reward_index: u8) -> Result<()> {
+        let farm = &mut ctx.accounts.farm;
+        let user_stake = &mut ctx.accounts.user_stake;
+        farm.reward(reward_index, ctx.accounts.reward_vault.key())?;
+        farm.update(Clock::get()?.unix_timestamp)?;
+        user_stake.settle(farm)?;
+
+        let amount = user_stake.rewards_owed[reward_index as usize];
+        user_stake.rewards_owed[reward_index as usize] = 0;
+
+        if amount > 0 {
+            // PDA signer (farm)
+            let seeds: &[&[u8]] = &[b"farm", farm.lp_mint.as_ref(), &[farm.bump]];
+            let signer = &[seeds];
+
+            // 보상 vault → 사용자
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.reward_vault.to_account_info(),
+                        mint: ctx.accounts.reward_mint.to_account_info(),
+                        to: ctx.accounts.user_reward_account.to_account_info(),
+                        authority: farm.to_account_info(),
+                    },
+                    signer,
+                ),
+                amount,
+                ctx.accounts.reward_mint.decimals,
+            )?;
+        }
+
+        msg!("Claimed {} of reward {}", amount, reward_index);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeFarm<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Farm::LEN,
+        seeds = [b"farm", lp_mint.key().as_ref()],
+        bump
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        has_one = lp_mint @ FarmError::InvalidLpMint,
+        has_one = admin @ FarmError::Unauthorized
+    )]
+    pub amm_info: Account<'info, AmmInfo>,
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        token::mint = lp_mint,
+        token::authority = farm,
+        token::token_program = token_program,
+        seeds = [b"lp_vault", farm.key().as_ref()],
+        bump
+    )]
+    pub lp_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AddReward<'info> {
+    #[account(mut, has_one = admin @ FarmError::Unauthorized)]
+    pub farm: Account<'info, Farm>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    // 같은 mint를 두 번 추가하면 vault 생성이 실패한다
+    #[account(
+        init,
+        payer = admin,
+        token::mint = reward_mint,
+        token::authority = farm,
+        token::token_program = token_program,
+        seeds = [b"reward_vault", farm.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Fund<'info> {
+    #[account(mut, has_one = admin @ FarmError::Unauthorized)]
+    pub farm: Account<'info, Farm>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = reward_mint)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub admin_reward_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetRate<'info> {
+    #[account(mut, has_one = admin @ FarmError::Unauthorized)]
+    pub farm: Account<'info, Farm>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateUserStake<'info> {
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = UserStake::LEN,
+        seeds = [b"user_stake", farm.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(
+        mut,
+        has_one = lp_mint @ FarmError::InvalidLpMint,
+        has_one = lp_vault @ FarmError::InvalidLpVault
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        mut,
+        has_one = farm @ FarmError::InvalidUserStake,
+        has_one = owner @ FarmError::Unauthorized
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub lp_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_lp_account.mint == lp_mint.key() @ FarmError::InvalidUserTokenMint,
+        constraint = user_lp_account.owner == owner.key() @ FarmError::InvalidUserTokenOwner
+    )]
+    pub user_lp_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(
+        mut,
+        has_one = lp_mint @ FarmError::InvalidLpMint,
+        has_one = lp_vault @ FarmError::InvalidLpVault
+    )]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        mut,
+        has_one = farm @ FarmError::InvalidUserStake,
+        has_one = owner @ FarmError::Unauthorized
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub lp_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_lp_account.mint == lp_mint.key() @ FarmError::InvalidUserTokenMint,
+        constraint = user_lp_account.owner == owner.key() @ FarmError::InvalidUserTokenOwner
+    )]
+    pub user_lp_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(mut)]
+    pub farm: Account<'info, Farm>,
+
+    #[account(
+        mut,
+        has_one = farm @ FarmError::InvalidUserStake,
+        has_one = owner @ FarmError::Unauthorized
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = reward_mint)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = user_reward_account.mint == reward_mint.key() @ FarmError::InvalidUserTokenMint,
+        constraint = user_reward_account.owner == owner.key() @ FarmError::InvalidUserTokenOwner
+    )]
+    pub user_reward_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[error_code]
+pub enum FarmError {
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Signer is not authorized")]
+    Unauthorized,
+    #[msg("LP mint does not match the pool or farm")]
+    InvalidLpMint,
+    #[msg("LP vault does not match the farm")]
+    InvalidLpVault,
+    #[msg("Farm already has the maximum number of rewards")]
+    TooManyRewards,
+    #[msg("Reward index is not registered")]
+    InvalidRewardIndex,
+    #[msg("Reward vault does not match the farm")]
+    InvalidRewardVault,
+    #[msg("User stake does not belong to this farm")]
+    InvalidUserStake,
+    #[msg("Unstake amount exceeds the staked amount")]
+    InsufficientStake,
+    #[msg("User token account mint mismatch")]
+    InvalidUserTokenMint,
+    #[msg("User token account owner mismatch")]
+    InvalidUserTokenOwner,
+}
@@ -0,0 +1,189 @@
+use crate::FarmError;
+use anchor_lang::prelude::*;
+
+/// farm 하나가 동시에 배분할 수 있는 보상 토큰 수
+pub const MAX_REWARDS: usize = 3;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RewardInfo {
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub emission_per_second: u64,
+    // fund 됐지만 아직 배분되지 않은 양. 배분은 이 안에서만 일어나므로 vault가 항상 지급분을 덮는다.
+    pub remaining: u64,
+    // 스테이크 1 단위당 누적 보상 (Q64.64)
+    pub reward_per_share_x64: u128,
+}
+
+impl RewardInfo {
+    // 계산: pubkey 2*32 + u64 2*8 + u128 16
+    pub const LEN: usize = 2 * 32 + 2 * 8 + 16; // = 96
+}
+
+#[account]
+pub struct Farm {
+    pub amm_info: Pubkey,
+    pub lp_mint: Pubkey,
+    pub lp_vault: Pubkey,
+    pub admin: Pubkey,
+    pub bump: u8,
+
+    pub total_staked: u64,
+    pub last_update_timestamp: i64,
+
+    pub reward_count: u8,
+    pub rewards: [RewardInfo; MAX_REWARDS],
+}
+
+impl Farm {
+    // 계산: discriminator(8) + pubkey 4*32 + u8 2 + u64 8 + i64 8 + RewardInfo 3*96
+    pub const LEN: usize = 8 + 4 * 32 + 2 + 8 + 8 + MAX_REWARDS * RewardInfo::LEN; // = 442
+
+    /// 등록된 보상 `index`와 그 vault 확인
+    pub fn reward(&self, index: u8, reward_vault: Pubkey) -> Result<&RewardInfo> {
+        require!(index < self.reward_count, FarmError::InvalidRewardIndex);
+        let reward = &self.rewards[index as usize];
+        require_keys_eq!(
+            reward.reward_vault,
+            reward_vault,
+            FarmError::InvalidRewardVault
+        );
+        Ok(reward)
+    }
+
+    /// 마지막 갱신 이후 `now`까지의 배출량을 누적값에 반영한다.
+    /// 스테이크가 없는 동안에는 배출하지 않고 남은 fund를 그대로 둔다.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        let elapsed = now.saturating_sub(self.last_update_timestamp).max(0) as u64;
+        self.last_update_timestamp = now;
+        if elapsed == 0 || self.total_staked == 0 {
+            return Ok(());
+        }
+
+        let count = self.reward_count as usize;
+        for reward in self.rewards[..count].iter_mut() {
+            let emitted = reward
+                .emission_per_second
+                .saturating_mul(elapsed)
+                .min(reward.remaining);
+            if emitted == 0 {
+                continue;
+            }
+            reward.remaining -= emitted;
+            // 나눗셈 나머지는 vault에 남는다
+            let delta = ((emitted as u128) << 64) / self.total_staked as u128;
+            reward.reward_per_share_x64 = reward
+                .reward_per_share_x64
+                .checked_add(delta)
+                .ok_or(FarmError::MathOverflow)?;
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct UserStake {
+    pub farm: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    // 마지막 정산 시점의 reward_per_share_x64
+    pub reward_per_share_paid_x64: [u128; MAX_REWARDS],
+    pub rewards_owed: [u64; MAX_REWARDS],
+}
+
+impl UserStake {
+    // 계산: discriminator(8) + pubkey 2*32 + u64 8 + u128 3*16 + u64 3*8
+    pub const LEN: usize = 8 + 2 * 32 + 8 + MAX_REWARDS * 16 + MAX_REWARDS * 8; // = 152
+
+    /// farm 누적값까지 쌓인 보상을 rewards_owed에 적립한다. `Farm::update` 뒤에 호출.
+    pub fn settle(&mut self, farm: &Farm) -> Result<()> {
+        for index in 0..farm.reward_count as usize {
+            let current = farm.rewards[index].reward_per_share_x64;
+            let pending =
+                pending_reward(self.amount, current - self.reward_per_share_paid_x64[index])?;
+            self.rewards_owed[index] = self.rewards_owed[index]
+                .checked_add(pending)
+                .ok_or(FarmError::MathOverflow)?;
+            self.reward_per_share_paid_x64[index] = current;
+        }
+        Ok(())
+    }
+}
+
+/// `amount * delta_x64 >> 64` (내림). delta를 상·하위 64비트로 나눠 u128 안에서 계산한다.
+pub fn pending_reward(amount: u64, delta_x64: u128) -> Result<u64> {
+    let high = (delta_x64 >> 64) * amount as u128;
+    let low = ((delta_x64 as u64) as u128 * amount as u128) >> 64;
+    u64::try_from(high + low).map_err(|_| error!(FarmError::MathOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn farm(total_staked: u64, emission_per_second: u64, remaining: u64) -> Farm {
+        let mut rewards = [RewardInfo::default(); MAX_REWARDS];
+        rewards[0].emission_per_second = emission_per_second;
+        rewards[0].remaining = remaining;
+        Farm {
+            amm_info: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            lp_vault: Pubkey::default(),
+            admin: Pubkey::default(),
+            bump: 0,
+            total_staked,
+            last_update_timestamp: 0,
+            reward_count: 1,
+            rewards,
+        }
+    }
+
+    fn stake(amount: u64) -> UserStake {
+        UserStake {
+            farm: Pubkey::default(),
+            owner: Pubkey::default(),
+            amount,
+            reward_per_share_paid_x64: [0; MAX_REWARDS],
+            rewards_owed: [0; MAX_REWARDS],
+        }
+    }
+
+    #[test]
+    fn splits_emissions_by_stake() {
+        let mut farm = farm(400, 100, 1_000_000);
+        let (mut alice, mut bob) = (stake(100), stake(300));
+        farm.update(10).unwrap();
+        alice.settle(&farm).unwrap();
+        bob.settle(&farm).unwrap();
+        assert_eq!(alice.rewards_owed[0], 250);
+        assert_eq!(bob.rewards_owed[0], 750);
+        assert_eq!(farm.rewards[0].remaining, 1_000_000 - 1_000);
+    }
+
+    #[test]
+    fn stops_when_funds_run_out() {
+        let mut farm = farm(3, 100, 500);
+        let mut user = stake(3);
+        farm.update(60).unwrap();
+        user.settle(&farm).unwrap();
+        assert_eq!(farm.rewards[0].remaining, 0);
+        // 나머지 때문에 지급액은 fund를 넘지 않는다
+        assert!(user.rewards_owed[0] <= 500 && user.rewards_owed[0] >= 499);
+    }
+
+    #[test]
+    fn keeps_funds_while_nothing_is_staked() {
+        let mut farm = farm(0, 100, 1_000);
+        farm.update(100).unwrap();
+        assert_eq!(farm.rewards[0].remaining, 1_000);
+        assert_eq!(farm.rewards[0].reward_per_share_x64, 0);
+        assert_eq!(farm.last_update_timestamp, 100);
+    }
+
+    #[test]
+    fn pending_reward_handles_large_values() {
+        assert_eq!(pending_reward(u64::MAX, 1 << 64).unwrap(), u64::MAX);
+        assert_eq!(pending_reward(3, (1 << 64) / 2).unwrap(), 1);
+        assert!(pending_reward(u64::MAX, 2 << 64).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+declare_id!("JBAypXAFEZaeV6TDUXPtDgYjvMYvjww1UNt1hKzGTDM6");
+
+/// `data[0]`이 이 값이면 원금 + 수수료를 상환, 아니면 상환하지 않는다 (테스트용)
+pub const REPAY: u8 = 1;
+/// `data[0]`이 이 값이면 amm_authority 권한으로 vault에서 인출을 시도한다 (테스트용, 항상 실패)
+pub const DRAIN: u8 = 2;
+
+/// ammv2 `flash_swap` 콜백 예제.
+#[program]
+pub mod flash_receiver {
+    use super::*;
+
+    /// ammv2가 빌려준 토큰을 받아 (필요하면 여기서 차익거래 등을 수행한 뒤) vault로 상환한다.
+    pub fn flash_swap_callback(
+        ctx: Context<FlashSwapCallback>,
+        initiator: Pubkey,
+        coin_amount: u64,
+        pc_amount: u64,
+        coin_fee: u64,
+        pc_fee: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        msg!(
+            "Flash swap for {}: {} coin, {} pc",
+            initiator,
+            coin_amount,
+            pc_amount
+        );
+        if data.first() == Some(&DRAIN) {
+            // amm_authority는 서명 없이 넘어오므로 런타임이 거부해야 한다
+            return token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.coin_vault.to_account_info(),
+                        mint: ctx.accounts.coin_mint.to_account_info(),
+                        to: ctx.accounts.receiver_coin_account.to_account_info(),
+                        authority: ctx.accounts.amm_authority.to_account_info(),
+                    },
+                ),
+                ctx.accounts.coin_vault.amount,
+                ctx.accounts.coin_mint.decimals,
+            );
+        }
+        if data.first() != Some(&REPAY) {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[u8]] = &[b"receiver_authority", &[ctx.bumps.receiver_authority]];
+        let signer = &[signer_seeds];
+
+        // receiver → vault (원금 + 수수료)
+        for (from, mint, to, amount, fee) in [
+            (
+                &ctx.accounts.receiver_coin_account,
+                &ctx.accounts.coin_mint,
+                &ctx.accounts.coin_vault,
+                coin_amount,
+                coin_fee,
+            ),
+            (
+                &ctx.accounts.receiver_pc_account,
+                &ctx.accounts.pc_mint,
+                &ctx.accounts.pc_vault,
+                pc_amount,
+                pc_fee,
+            ),
+        ] {
+            if amount > 0 {
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        TransferChecked {
+                            from: from.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: ctx.accounts.receiver_authority.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    amount
+                        .checked_add(fee)
+                        .ok_or(ReceiverError::InvalidAmount)?,
+                    mint.decimals,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 계정 순서는 ammv2 `flash_swap`이 넘기는 순서와 같다:
+/// amm_authority 다음에 flash_swap 호출 시 remaining accounts로 넘긴 계정들.
+/// token_program은 풀이 쓰는 프로그램(`amm_info.token_program`)을 그대로 넘긴다 — vault와 mint의
+/// 소유 프로그램과 같아야 한다.
+/// amm_authority는 서명자가 아니므로 누구나 이 콜백을 직접 호출할 수 있다 — receiver 계정에는
+/// 수수료로 쓸 만큼만 둔다.
+#[derive(Accounts)]
+pub struct FlashSwapCallback<'info> {
+    /// CHECK: Pool authority passed without a signature; only compared with the vault owners.
+    pub amm_authority: UncheckedAccount<'info>,
+
+    /// CHECK: PDA owning the receiver token accounts, verified via seeds.
+    #[account(seeds = [b"receiver_authority"], bump)]
+    pub receiver_authority: AccountInfo<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = token_program)]
+    pub pc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = coin_mint,
+        token::token_program = token_program,
+        constraint = coin_vault.owner == amm_authority.key() @ ReceiverError::InvalidCaller
+    )]
+    pub coin_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = pc_mint,
+        token::token_program = token_program,
+        constraint = pc_vault.owner == amm_authority.key() @ ReceiverError::InvalidCaller
+    )]
+    pub pc_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = coin_mint,
+        token::authority = receiver_authority,
+        token::token_program = token_program
+    )]
+    pub receiver_coin_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = pc_mint,
+        token::authority = receiver_authority,
+        token::token_program = token_program
+    )]
+    pub receiver_pc_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[error_code]
+pub enum ReceiverError {
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Vault is not owned by the pool authority")]
+    InvalidCaller,
+}
@@ -0,0 +1,288 @@
+use ammv2::program::SolanaDex;
+use ammv2::{AmmInfo, SwapDirection};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+declare_id!("E2axiq5PHfMm2K9dD5C6xFyAAeBMe41uS2rAVhJtYts3");
+
+/// hop 하나당 remaining accounts 수:
+/// amm_info, amm_authority, coin_mint, pc_mint, coin_vault, pc_vault, router_coin_account, router_pc_account,
+/// token_program (풀의 `amm_info.token_program`과 같아야 한다),
+/// user_refund_account (hop 입력 mint의 사용자 계정 — lot 나머지를 돌려받는다)
+pub const ACCOUNTS_PER_HOP: usize = 10;
+pub const MAX_HOPS: usize = 4;
+
+#[program]
+pub mod router {
+    use super::*;
+
+    /// router_authority가 소유하는 mint별 중간 계정 생성 (누구나 호출 가능)
+    pub fn initialize_hop_account(ctx: Context<InitializeHopAccount>) -> Result<()> {
+        msg!("Hop account created for mint {}", ctx.accounts.mint.key());
+        Ok(())
+    }
+
+    /// A → B → ... → Z 경로로 ammv2 풀들을 거쳐 스왑한다.
+    ///
+    /// 입력 토큰은 먼저 router의 hop 계정으로 옮겨지고, 각 hop은 router_authority가
+    /// `solana_dex::swap_base_in`을 CPI로 호출한다. 중간 수량은 router 계정에만 머물며
+    /// 마지막 hop의 결과만 사용자에게 전송된 뒤 `minimum_amount_out`과 비교된다.
+    /// Token-2022 transfer fee가 있으면 각 단계에서 실제로 도착한 양을 다음 단계에 쓴다.
+    /// 토큰 프로그램은 hop마다 받으므로 SPL Token 풀과 Token-2022 풀을 한 경로에 섞을 수 있다.
+    /// 각 hop의 입력은 그 풀의 lot 단위로 내림하고, 나머지는 hop의 `user_refund_account`로 돌려준다.
+    pub fn route_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RouteSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        deadline: Option<i64>,
+    ) -> Result<()> {
+        let hops = ctx.remaining_accounts.chunks_exact(ACCOUNTS_PER_HOP);
+        require!(
+            hops.len() > 0 && hops.remainder().is_empty(),
+            RouterError::InvalidPath
+        );
+        require!(hops.len() <= MAX_HOPS, RouterError::TooManyHops);
+        require!(amount_in > 0, RouterError::InvalidAmount);
+
+        let signer_seeds: &[&[u8]] = &[b"router_authority", &[ctx.bumps.router_authority]];
+        let signer = &[signer_seeds];
+
+        let mut input_mint = ctx.accounts.user_source_account.mint;
+        let mut amount = amount_in;
+        let mut previous_output: Option<&AccountInfo<'info>> = None;
+        let mut output_mint: Option<&AccountInfo<'info>> = None;
+        let mut output_token_program: Option<&AccountInfo<'info>> = None;
+
+        for (index, hop) in hops.enumerate() {
+            let pool = load_pool(&hop[0])?;
+            let token_program = &hop[8];
+            require_keys_eq!(
+                pool.token_program,
+                token_program.key(),
+                RouterError::InvalidTokenProgram
+            );
+
+            let coin_mint = hop[2].key();
+            let pc_mint = hop[3].key();
+            let direction = if input_mint == coin_mint {
+                SwapDirection::CoinToPc
+            } else if input_mint == pc_mint {
+                SwapDirection::PcToCoin
+            } else {
+                return err!(RouterError::InvalidPath);
+            };
+            let (hop_source, hop_destination) = direction.orient(&hop[6], &hop[7]);
+            let (hop_input_mint, hop_output_mint) = direction.orient(&hop[2], &hop[3]);
+
+            match previous_output {
+                // 첫 hop: 사용자 → router hop 계정
+                None => {
+                    let before = token_amount(hop_source)?;
+                    token_interface::transfer_checked(
+                        CpiContext::new(
+                            token_program.clone(),
+                            TransferChecked {
+                                from: ctx.accounts.user_source_account.to_account_info(),
+                                mint: hop_input_mint.clone(),
+                                to: hop_source.clone(),
+                                authority: ctx.accounts.user_authority.to_account_info(),
+                            },
+                        ),
+                        amount,
+                        mint_decimals(hop_input_mint)?,
+                    )?;
+                    // transfer fee가 있으면 hop 계정에 실제로 도착한 양만 스왑한다
+                    amount = token_amount(hop_source)?
+                        .checked_sub(before)
+                        .ok_or(RouterError::InvalidAmount)?;
+                }
+                // 이후 hop: 이전 hop의 출력 계정을 그대로 입력으로 사용해야 한다
+                Some(previous) => {
+                    require_keys_eq!(previous.key(), hop_source.key(), RouterError::InvalidPath)
+                }
+            }
+
+            // 풀은 lot 단위로만 받으므로 나머지는 hop 계정에 남기지 않고 사용자에게 돌려준다
+            let (lot_size, _) = pool.lot_sizes(direction);
+            let remainder = amount % lot_size;
+            if remainder > 0 {
+                let refund = &hop[9];
+                let refund_account = token_account(refund)?;
+                require_keys_eq!(
+                    refund_account.owner,
+                    ctx.accounts.user_authority.key(),
+                    RouterError::InvalidOwner
+                );
+                require_keys_eq!(refund_account.mint, input_mint, RouterError::InvalidPath);
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        token_program.clone(),
+                        TransferChecked {
+                            from: hop_source.clone(),
+                            mint: hop_input_mint.clone(),
+                            to: refund.clone(),
+                            authority: ctx.accounts.router_authority.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    remainder,
+                    mint_decimals(hop_input_mint)?,
+                )?;
+                amount -= remainder;
+            }
+            require!(amount > 0, RouterError::InvalidAmount);
+
+            let before = token_amount(hop_destination)?;
+            ammv2::cpi::swap_base_in(
+                CpiContext::new_with_signer(
+                    ctx.accounts.ammv2_program.to_account_info(),
+                    ammv2::cpi::accounts::SwapBaseIn {
+                        amm_info: hop[0].clone(),
+                        amm_authority: hop[1].clone(),
+                        coin_mint: hop[2].clone(),
+                        pc_mint: hop[3].clone(),
+                        coin_vault: hop[4].clone(),
+                        pc_vault: hop[5].clone(),
+                        user_coin_account: hop[6].clone(),
+                        user_pc_account: hop[7].clone(),
+                        user_authority: ctx.accounts.router_authority.to_account_info(),
+                        token_program: token_program.clone(),
+                    },
+                    signer,
+                ),
+                amount,
+                0,
+                direction,
+                deadline,
+            )?;
+            let received = token_amount(hop_destination)?
+                .checked_sub(before)
+                .ok_or(RouterError::InvalidAmount)?;
+
+            msg!("Hop {}: {} -> {}", index, amount, received);
+            amount = received;
+            input_mint = direction.orient(coin_mint, pc_mint).1;
+            previous_output = Some(hop_destination);
+            output_mint = Some(hop_output_mint);
+            output_token_program = Some(token_program);
+        }
+
+        require_keys_eq!(
+            ctx.accounts.user_destination_account.mint,
+            input_mint,
+            RouterError::InvalidPath
+        );
+
+        // router 마지막 hop 계정 → 사용자
+        let final_account = previous_output.ok_or(RouterError::InvalidPath)?;
+        let final_mint = output_mint.ok_or(RouterError::InvalidPath)?;
+        let final_token_program = output_token_program.ok_or(RouterError::InvalidPath)?;
+        let destination = ctx.accounts.user_destination_account.to_account_info();
+        let before = token_amount(&destination)?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                final_token_program.clone(),
+                TransferChecked {
+                    from: final_account.clone(),
+                    mint: final_mint.clone(),
+                    to: destination.clone(),
+                    authority: ctx.accounts.router_authority.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+            mint_decimals(final_mint)?,
+        )?;
+
+        // 최종 수량만 슬리피지 검사 (transfer fee를 뗀, 사용자에게 도착한 양)
+        let amount_out = token_amount(&destination)?
+            .checked_sub(before)
+            .ok_or(RouterError::InvalidAmount)?;
+        require!(
+            amount_out >= minimum_amount_out,
+            RouterError::SlippageExceeded
+        );
+
+        msg!("Routed {} for {}", amount_in, amount_out);
+        Ok(())
+    }
+}
+
+fn token_account(info: &AccountInfo) -> Result<TokenAccount> {
+    let data = info.try_borrow_data()?;
+    TokenAccount::try_deserialize(&mut &data[..])
+}
+
+fn token_amount(info: &AccountInfo) -> Result<u64> {
+    Ok(token_account(info)?.amount)
+}
+
+/// hop의 풀 상태. ammv2가 소유한 `AmmInfo`만 받는다
+fn load_pool(info: &AccountInfo) -> Result<AmmInfo> {
+    require_keys_eq!(*info.owner, ammv2::ID, RouterError::InvalidPath);
+    let data = info.try_borrow_data()?;
+    AmmInfo::try_deserialize(&mut &data[..])
+}
+
+fn mint_decimals(info: &AccountInfo) -> Result<u8> {
+    let data = info.try_borrow_data()?;
+    Ok(Mint::try_deserialize(&mut &data[..])?.decimals)
+}
+
+#[derive(Accounts)]
+pub struct InitializeHopAccount<'info> {
+    /// CHECK: PDA authority for every router hop account, verified via seeds.
+    #[account(seeds = [b"router_authority"], bump)]
+    pub router_authority: AccountInfo<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        token::mint = mint,
+        token::authority = router_authority,
+        seeds = [b"hop_account", mint.key().as_ref()],
+        bump
+    )]
+    pub hop_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RouteSwap<'info> {
+    /// CHECK: PDA authority that signs every hop, verified via seeds.
+    #[account(seeds = [b"router_authority"], bump)]
+    pub router_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = user_source_account.owner == user_authority.key() @ RouterError::InvalidOwner
+    )]
+    pub user_source_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub user_destination_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub user_authority: Signer<'info>,
+    pub ammv2_program: Program<'info, SolanaDex>,
+}
+
+#[error_code]
+pub enum RouterError {
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Swap path does not connect")]
+    InvalidPath,
+    #[msg("Too many hops")]
+    TooManyHops,
+    #[msg("Slippage tolerance exceeded")]
+    SlippageExceeded,
+    #[msg("Source account is not owned by the signer")]
+    InvalidOwner,
+    #[msg("Token program does not match the pool")]
+    InvalidTokenProgram,
+}
@@ -0,0 +1,140 @@
+//! `migrate_vaults` 통합 테스트용 이전 레이아웃 풀을 test validator 계정 파일로 쓴다.
+//!
+//! ```text
+//! cargo run -p solana-dex-sdk --example legacy_pool_fixtures
+//! ```
+//!
+//! 이전 버전 `initialize_pool`이 만든 것과 같은 계정들 — `AmmInfo::LEGACY_LEN` 크기의 `amm_info`,
+//! mint 단위 vault(`[b"coin_vault", coin_mint]`), LP mint — 을 `tests/fixtures/legacy_pool/`에
+//! `solana account --output json` 형식으로 기록한다. `Anchor.toml`의 `[[test.validator.account]]`가
+//! 이 파일들을 불러온다.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_dex_sdk::{pda, AmmInfo, PoolKeys, PROGRAM_ID};
+use std::fs;
+use std::path::Path;
+
+// 이전 버전은 스왑 후 유동성을 기록하지 않았다 — 기록은 공급 시점, vault는 스왑 이후 잔고
+const RECORDED_AMOUNT: u64 = 1_000_000;
+const COIN_VAULT_AMOUNT: u64 = 1_010_000;
+const PC_VAULT_AMOUNT: u64 = 990_150;
+const LP_SUPPLY: u64 = 1_000_000;
+
+fn main() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/legacy_pool");
+    fs::create_dir_all(&dir).unwrap();
+
+    let (coin_mint, pc_mint) =
+        pda::sorted_mints(label("legacy_coin_mint"), label("legacy_pc_mint"));
+    let pool = PoolKeys::derive(coin_mint, pc_mint, spl_token::ID);
+    let coin_vault = pda::legacy_vault(b"coin_vault", &coin_mint).0;
+    let pc_vault = pda::legacy_vault(b"pc_vault", &pc_mint).0;
+
+    let mut state = AmmInfo::try_deserialize_unchecked(&mut &[0u8; AmmInfo::LEN][..]).unwrap();
+    state.coin_mint = coin_mint;
+    state.pc_mint = pc_mint;
+    state.coin_vault = coin_vault;
+    state.pc_vault = pc_vault;
+    state.lp_mint = pool.lp_mint;
+    state.pool_coin_amount = RECORDED_AMOUNT;
+    state.pool_pc_amount = RECORDED_AMOUNT;
+    state.pool_lp_amount = LP_SUPPLY;
+    state.min_size = 1;
+    state.coin_lot_size = 1;
+    state.pc_lot_size = 1;
+    state.amm_coin_account = coin_vault;
+    state.amm_pc_account = pc_vault;
+    let mut amm_info = Vec::new();
+    state.try_serialize(&mut amm_info).unwrap();
+    amm_info.truncate(AmmInfo::LEGACY_LEN);
+
+    let accounts = [
+        ("amm_info", pool.amm_info, PROGRAM_ID, amm_info),
+        (
+            "coin_mint",
+            coin_mint,
+            spl_token::ID,
+            mint(COIN_VAULT_AMOUNT, COption::None),
+        ),
+        (
+            "pc_mint",
+            pc_mint,
+            spl_token::ID,
+            mint(PC_VAULT_AMOUNT, COption::None),
+        ),
+        (
+            "lp_mint",
+            pool.lp_mint,
+            spl_token::ID,
+            mint(LP_SUPPLY, COption::Some(pool.amm_authority)),
+        ),
+        (
+            "coin_vault",
+            coin_vault,
+            spl_token::ID,
+            token_account(coin_mint, pool.amm_authority, COIN_VAULT_AMOUNT),
+        ),
+        (
+            "pc_vault",
+            pc_vault,
+            spl_token::ID,
+            token_account(pc_mint, pool.amm_authority, PC_VAULT_AMOUNT),
+        ),
+    ];
+    for (name, address, owner, data) in accounts {
+        let json = serde_json::json!({
+            "pubkey": address.to_string(),
+            "account": {
+                "lamports": Rent::default().minimum_balance(data.len()),
+                "data": [STANDARD.encode(&data), "base64"],
+                "owner": owner.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": data.len(),
+            },
+        });
+        let path = dir.join(format!("{name}.json"));
+        fs::write(&path, serde_json::to_string_pretty(&json).unwrap() + "\n").unwrap();
+        println!("{name}: {address}");
+    }
+}
+
+// 고정 주소 (mint 계정은 서명할 일이 없으므로 키쌍이 필요 없다)
+fn label(name: &str) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[name.as_bytes()]).to_bytes())
+}
+
+fn mint(supply: u64, mint_authority: COption<Pubkey>) -> Vec<u8> {
+    let mut data = vec![0u8; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority,
+        supply,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> Vec<u8> {
+    let mut data = vec![0u8; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    data
+}
@@ -0,0 +1,629 @@
+//! `solana_dex` instruction 빌더. 계정 순서와 인자 직렬화는 프로그램 crate의
+//! `ammv2::accounts` / `ammv2::instruction` 타입이 만든다.
+
+use crate::pda::{self, PoolKeys, UserAccounts};
+use crate::{
+    AmmConfig, AmmConfigParams, CurveType, PoolParams, PoolSide, PoolStatus, SwapDirection,
+    PROGRAM_ID,
+};
+use ammv2::{accounts, instruction as ix};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, InstructionData};
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `admin`은 ammv2 프로그램의 업그레이드 권한자여야 한다
+pub fn initialize_amm_config(admin: Pubkey, params: AmmConfigParams) -> Instruction {
+    build(
+        accounts::InitializeAmmConfig {
+            amm_config: pda::amm_config().0,
+            program: PROGRAM_ID,
+            program_data: pda::program_data().0,
+            admin,
+            system_program: system_program::ID,
+        },
+        ix::InitializeAmmConfig { params },
+    )
+}
+
+pub fn update_amm_config(admin: Pubkey, params: AmmConfigParams) -> Instruction {
+    build(
+        accounts::UpdateAmmConfig {
+            amm_config: pda::amm_config().0,
+            admin,
+        },
+        ix::UpdateAmmConfig { params },
+    )
+}
+
+/// `config`는 조회한 `AmmConfig` — 생성 수수료 수령 계정과 레지스트리 페이지를 정한다.
+/// `fee_bps`가 `None`이면 config의 기본 티어.
+pub fn initialize_pool(
+    pool: &PoolKeys,
+    config: &AmmConfig,
+    payer: Pubkey,
+    open_time: u64,
+    fee_bps: Option<u64>,
+    curve_type: CurveType,
+    amp: u64,
+) -> Instruction {
+    build(
+        accounts::InitializePool {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            lp_mint: pool.lp_mint,
+            locked_lp_vault: pool.locked_lp_vault,
+            amm_config: pda::amm_config().0,
+            pool_registry: pda::pool_registry(config.registry_page()).0,
+            payer,
+            fee_receiver: config.fee_receiver,
+            system_program: system_program::ID,
+            token_program: pool.token_program,
+            rent: sysvar::rent::ID,
+        },
+        ix::InitializePool {
+            open_time,
+            fee_bps,
+            curve_type,
+            amp,
+        },
+    )
+}
+
+pub fn deposit(
+    pool: &PoolKeys,
+    user: &UserAccounts,
+    max_coin_amount: u64,
+    max_pc_amount: u64,
+    base_side: u64,
+    deadline: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::Deposit {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            lp_mint: pool.lp_mint,
+            locked_lp_vault: pool.locked_lp_vault,
+            user_coin_account: user.coin_account,
+            user_pc_account: user.pc_account,
+            user_lp_account: user.lp_account,
+            user_authority: user.authority,
+            token_program: pool.token_program,
+        },
+        ix::Deposit {
+            max_coin_amount,
+            max_pc_amount,
+            base_side,
+            deadline,
+        },
+    )
+}
+
+/// `side` 쪽 사용자 계정에서 `amount_in`을 낸다
+pub fn deposit_single_side(
+    pool: &PoolKeys,
+    user: &UserAccounts,
+    amount_in: u64,
+    side: PoolSide,
+    min_lp_out: u64,
+    deadline: Option<i64>,
+) -> Instruction {
+    let user_source_account = match side {
+        PoolSide::Coin => user.coin_account,
+        PoolSide::Pc => user.pc_account,
+    };
+    build(
+        accounts::DepositSingleSide {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            lp_mint: pool.lp_mint,
+            user_source_account,
+            user_lp_account: user.lp_account,
+            user_authority: user.authority,
+            token_program: pool.token_program,
+        },
+        ix::DepositSingleSide {
+            amount_in,
+            side,
+            min_lp_out,
+            deadline,
+        },
+    )
+}
+
+pub fn swap_base_in(
+    pool: &PoolKeys,
+    user: &UserAccounts,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    direction: SwapDirection,
+    deadline: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::SwapBaseIn {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            user_coin_account: user.coin_account,
+            user_pc_account: user.pc_account,
+            user_authority: user.authority,
+            token_program: pool.token_program,
+        },
+        ix::SwapBaseIn {
+            amount_in,
+            minimum_amount_out,
+            direction,
+            deadline,
+        },
+    )
+}
+
+pub fn swap_base_out(
+    pool: &PoolKeys,
+    user: &UserAccounts,
+    amount_out: u64,
+    max_amount_in: u64,
+    direction: SwapDirection,
+    deadline: Option<i64>,
+) -> Instruction {
+    build(
+        accounts::SwapBaseOut {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            user_coin_account: user.coin_account,
+            user_pc_account: user.pc_account,
+            user_authority: user.authority,
+            token_program: pool.token_program,
+        },
+        ix::SwapBaseOut {
+            amount_out,
+            max_amount_in,
+            direction,
+            deadline,
+        },
+    )
+}
+
+fn withdraw_accounts(pool: &PoolKeys, user: &UserAccounts) -> accounts::Withdraw {
+    accounts::Withdraw {
+        amm_info: pool.amm_info,
+        amm_authority: pool.amm_authority,
+        coin_mint: pool.coin_mint,
+        pc_mint: pool.pc_mint,
+        lp_mint: pool.lp_mint,
+        coin_vault: pool.coin_vault,
+        pc_vault: pool.pc_vault,
+        user_coin_account: user.coin_account,
+        user_pc_account: user.pc_account,
+        user_lp_account: user.lp_account,
+        user_authority: user.authority,
+        token_program: pool.token_program,
+    }
+}
+
+pub fn withdraw(
+    pool: &PoolKeys,
+    user: &UserAccounts,
+    amount: u64,
+    min_coin_amount: u64,
+    min_pc_amount: u64,
+    deadline: Option<i64>,
+) -> Instruction {
+    build(
+        withdraw_accounts(pool, user),
+        ix::Withdraw {
+            amount,
+            min_coin_amount,
+            min_pc_amount,
+            deadline,
+        },
+    )
+}
+
+pub fn withdraw_single_side(
+    pool: &PoolKeys,
+    user: &UserAccounts,
+    amount: u64,
+    side: PoolSide,
+    min_amount_out: u64,
+    deadline: Option<i64>,
+) -> Instruction {
+    build(
+        withdraw_accounts(pool, user),
+        ix::WithdrawSingleSide {
+            amount,
+            side,
+            min_amount_out,
+            deadline,
+        },
+    )
+}
+
+/// flash swap 수신 쪽 계정. `remaining_accounts`는 콜백에 amm_authority 다음 순서로 전달된다.
+#[derive(Clone, Debug)]
+pub struct FlashReceiver {
+    pub program: Pubkey,
+    pub coin_account: Pubkey,
+    pub pc_account: Pubkey,
+    pub remaining_accounts: Vec<AccountMeta>,
+}
+
+pub fn flash_swap(
+    pool: &PoolKeys,
+    receiver: &FlashReceiver,
+    initiator: Pubkey,
+    coin_amount_out: u64,
+    pc_amount_out: u64,
+    data: Vec<u8>,
+) -> Instruction {
+    let mut instruction = build(
+        accounts::FlashSwap {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            lp_mint: pool.lp_mint,
+            locked_lp_vault: pool.locked_lp_vault,
+            receiver_coin_account: receiver.coin_account,
+            receiver_pc_account: receiver.pc_account,
+            callback_program: receiver.program,
+            initiator,
+            token_program: pool.token_program,
+        },
+        ix::FlashSwap {
+            coin_amount_out,
+            pc_amount_out,
+            data,
+        },
+    );
+    instruction
+        .accounts
+        .extend(receiver.remaining_accounts.iter().cloned());
+    instruction
+}
+
+pub fn ramp_amp(
+    pool: &PoolKeys,
+    admin: Pubkey,
+    target_amp: u64,
+    ramp_stop_timestamp: i64,
+) -> Instruction {
+    build(
+        accounts::RampAmp {
+            amm_info: pool.amm_info,
+            admin,
+        },
+        ix::RampAmp {
+            target_amp,
+            ramp_stop_timestamp,
+        },
+    )
+}
+
+pub fn stop_ramp_amp(pool: &PoolKeys, admin: Pubkey) -> Instruction {
+    build(
+        accounts::StopRampAmp {
+            amm_info: pool.amm_info,
+            admin,
+        },
+        ix::StopRampAmp {},
+    )
+}
+
+/// 반환값(`Observation`)은 시뮬레이션 결과의 return data로 읽는다
+pub fn observe(pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::Observe {
+            amm_info: pool.amm_info,
+        },
+        ix::Observe {},
+    )
+}
+
+/// vault에 풀 유동성보다 많이 들어온 잔여분을 recipient 계정으로 보낸다 (permissionless)
+pub fn skim(
+    pool: &PoolKeys,
+    recipient_coin_account: Pubkey,
+    recipient_pc_account: Pubkey,
+) -> Instruction {
+    build(
+        accounts::Skim {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            recipient_coin_account,
+            recipient_pc_account,
+            token_program: pool.token_program,
+        },
+        ix::Skim {},
+    )
+}
+
+/// 풀 유동성을 현재 vault 잔고에 맞춘다 (permissionless)
+pub fn sync(pool: &PoolKeys) -> Instruction {
+    build(
+        accounts::SyncReserves {
+            amm_info: pool.amm_info,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+        },
+        ix::Sync {},
+    )
+}
+
+pub fn set_protocol_fee(
+    pool: &PoolKeys,
+    admin: Pubkey,
+    protocol_fee_numerator: u64,
+) -> Instruction {
+    build(
+        accounts::SetProtocolFee {
+            amm_info: pool.amm_info,
+            admin,
+        },
+        ix::SetProtocolFee {
+            protocol_fee_numerator,
+        },
+    )
+}
+
+pub fn set_pool_status(pool: &PoolKeys, admin: Pubkey, status: PoolStatus) -> Instruction {
+    build(
+        accounts::SetPoolStatus {
+            amm_info: pool.amm_info,
+            admin,
+        },
+        ix::SetPoolStatus { status },
+    )
+}
+
+pub fn update_pool_params(pool: &PoolKeys, admin: Pubkey, params: PoolParams) -> Instruction {
+    build(
+        accounts::UpdatePoolParams {
+            amm_info: pool.amm_info,
+            admin,
+        },
+        ix::UpdatePoolParams { params },
+    )
+}
+
+pub fn propose_admin(pool: &PoolKeys, admin: Pubkey, new_admin: Pubkey) -> Instruction {
+    build(
+        accounts::ProposeAdmin {
+            amm_info: pool.amm_info,
+            admin,
+        },
+        ix::ProposeAdmin { new_admin },
+    )
+}
+
+pub fn accept_admin(pool: &PoolKeys, pending_admin: Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAdmin {
+            amm_info: pool.amm_info,
+            pending_admin,
+        },
+        ix::AcceptAdmin {},
+    )
+}
+
+pub fn collect_protocol_fees(
+    pool: &PoolKeys,
+    admin: Pubkey,
+    treasury_coin_account: Pubkey,
+    treasury_pc_account: Pubkey,
+) -> Instruction {
+    build(
+        accounts::CollectProtocolFees {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            treasury_coin_account,
+            treasury_pc_account,
+            admin,
+            token_program: pool.token_program,
+        },
+        ix::CollectProtocolFees {},
+    )
+}
+
+/// 이전 레이아웃 풀의 mint 단위 vault를 풀 단위 vault로 옮긴다. `pool`은 `PoolKeys::derive`로
+/// 만든 (새 vault 주소를 가진) 값, `config`는 조회한 `AmmConfig`이며 `admin`은 config admin이어야 한다.
+pub fn migrate_vaults(pool: &PoolKeys, config: &AmmConfig, admin: Pubkey) -> Instruction {
+    build(
+        accounts::MigrateVaults {
+            amm_info: pool.amm_info,
+            amm_authority: pool.amm_authority,
+            coin_mint: pool.coin_mint,
+            pc_mint: pool.pc_mint,
+            legacy_coin_vault: pda::legacy_vault(b"coin_vault", &pool.coin_mint).0,
+            legacy_pc_vault: pda::legacy_vault(b"pc_vault", &pool.pc_mint).0,
+            coin_vault: pool.coin_vault,
+            pc_vault: pool.pc_vault,
+            lp_mint: pool.lp_mint,
+            locked_lp_vault: pool.locked_lp_vault,
+            amm_config: pda::amm_config().0,
+            pool_registry: pda::pool_registry(config.registry_page()).0,
+            admin,
+            system_program: system_program::ID,
+            token_program: pool.token_program,
+        },
+        ix::MigrateVaults {},
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Discriminator;
+    use anchor_spl::token::ID as TOKEN_PROGRAM_ID;
+
+    fn pool() -> PoolKeys {
+        PoolKeys::derive(Pubkey::new_unique(), Pubkey::new_unique(), TOKEN_PROGRAM_ID)
+    }
+
+    #[test]
+    fn swap_marks_signer_and_writable_accounts() {
+        let pool = pool();
+        let user = UserAccounts::associated(Pubkey::new_unique(), &pool);
+        let instruction = swap_base_in(&pool, &user, 10, 9, SwapDirection::CoinToPc, None);
+
+        assert_eq!(instruction.program_id, PROGRAM_ID);
+        assert_eq!(&instruction.data[..8], ix::SwapBaseIn::DISCRIMINATOR);
+        let signers: Vec<_> = instruction
+            .accounts
+            .iter()
+            .filter(|meta| meta.is_signer)
+            .collect();
+        assert_eq!(signers.len(), 1);
+        assert_eq!(signers[0].pubkey, user.authority);
+        assert!(instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == pool.coin_vault && meta.is_writable));
+    }
+
+    #[test]
+    fn initialize_amm_config_passes_the_program_data() {
+        let admin = Pubkey::new_unique();
+        let params = AmmConfigParams {
+            fee_receiver: admin,
+            default_trade_fee_bps: 30,
+            default_protocol_fee_numerator: 0,
+            creation_fee: 0,
+        };
+        let instruction = initialize_amm_config(admin, params);
+        let keys: Vec<_> = instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert!(keys.contains(&PROGRAM_ID));
+        assert!(keys.contains(&pda::program_data().0));
+    }
+
+    #[test]
+    fn single_side_deposit_pays_from_the_chosen_side() {
+        let pool = pool();
+        let user = UserAccounts::associated(Pubkey::new_unique(), &pool);
+        let instruction = deposit_single_side(&pool, &user, 100, PoolSide::Pc, 1, None);
+        let keys: Vec<_> = instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert!(keys.contains(&user.pc_account));
+        assert!(!keys.contains(&user.coin_account));
+    }
+
+    #[test]
+    fn initialize_pool_uses_the_current_registry_page() {
+        let (coin_mint, pc_mint) = pda::sorted_mints(Pubkey::new_unique(), Pubkey::new_unique());
+        let pool = PoolKeys::derive(coin_mint, pc_mint, TOKEN_PROGRAM_ID);
+        let config = AmmConfig {
+            admin: Pubkey::new_unique(),
+            fee_receiver: Pubkey::new_unique(),
+            default_trade_fee_bps: 30,
+            default_protocol_fee_numerator: 0,
+            creation_fee: 0,
+            pool_count: crate::POOLS_PER_PAGE + 1,
+        };
+        let instruction = initialize_pool(
+            &pool,
+            &config,
+            Pubkey::new_unique(),
+            0,
+            None,
+            CurveType::ConstantProduct,
+            0,
+        );
+        let keys: Vec<_> = instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert!(keys.contains(&pda::pool_registry(1).0));
+        assert!(keys.contains(&config.fee_receiver));
+        assert!(pool.coin_mint < pool.pc_mint);
+    }
+
+    #[test]
+    fn migrate_vaults_moves_from_the_mint_vaults() {
+        let pool = pool();
+        let config = AmmConfig {
+            admin: Pubkey::new_unique(),
+            fee_receiver: Pubkey::new_unique(),
+            default_trade_fee_bps: 30,
+            default_protocol_fee_numerator: 0,
+            creation_fee: 0,
+            pool_count: 0,
+        };
+        let instruction = migrate_vaults(&pool, &config, config.admin);
+        let writable: Vec<_> = instruction
+            .accounts
+            .iter()
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        for key in [
+            pool.amm_info,
+            pda::legacy_vault(b"coin_vault", &pool.coin_mint).0,
+            pda::legacy_vault(b"pc_vault", &pool.pc_mint).0,
+            pool.coin_vault,
+            pool.pc_vault,
+            pool.locked_lp_vault,
+            pda::pool_registry(0).0,
+        ] {
+            assert!(writable.contains(&key));
+        }
+    }
+
+    #[test]
+    fn flash_swap_appends_remaining_accounts() {
+        let pool = pool();
+        let extra = AccountMeta::new(Pubkey::new_unique(), false);
+        let receiver = FlashReceiver {
+            program: Pubkey::new_unique(),
+            coin_account: Pubkey::new_unique(),
+            pc_account: Pubkey::new_unique(),
+            remaining_accounts: vec![extra.clone()],
+        };
+        let instruction = flash_swap(&pool, &receiver, Pubkey::new_unique(), 1, 0, vec![]);
+        assert_eq!(instruction.accounts.last(), Some(&extra));
+    }
+}
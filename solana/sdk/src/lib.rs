@@ -0,0 +1,47 @@
+//! ammv2 (`solana_dex`) 오프체인 클라이언트.
+//!
+//! - [`pda`]: 계정 주소 유도 (`amm_config`, 레지스트리 페이지, `amm_info`, `amm_authority`, vault,
+//!   `lp_mint`, `locked_lp`)
+//! - [`quote`]: 온체인과 같은 곡선/수수료 코드로 계산한 스왑 견적
+//! - [`instruction`]: 모든 `solana_dex` instruction의 `Instruction` 빌더
+//!
+//! 온체인 타입(`AmmInfo`, `AmmConfig`, `PoolRegistry`, `SwapDirection`, `PoolParams` 등)은
+//! 프로그램 crate를 그대로 다시 내보낸다.
+
+pub mod instruction;
+pub mod pda;
+pub mod quote;
+
+pub use ammv2::{
+    AmmConfig, AmmConfigParams, AmmInfo, CurveType, DexError, PoolEntry, PoolParams, PoolRegistry,
+    PoolSide, PoolStatus, SwapDirection, ID as PROGRAM_ID, POOLS_PER_PAGE,
+};
+pub use pda::{PoolKeys, UserAccounts};
+
+use anchor_lang::AccountDeserialize;
+
+/// `amm_info` 계정 데이터를 `AmmInfo`로 읽는다 (discriminator 검사 포함).
+/// `migrate_vaults` 이전 레이아웃은 새 필드를 0으로 채워 읽는다 — [`is_legacy_amm_info`]로 구분한다.
+pub fn decode_amm_info(data: &[u8]) -> anchor_lang::Result<AmmInfo> {
+    if is_legacy_amm_info(data) {
+        let mut padded = data.to_vec();
+        padded.resize(AmmInfo::LEN, 0);
+        return AmmInfo::try_deserialize(&mut &padded[..]);
+    }
+    AmmInfo::try_deserialize(&mut &data[..])
+}
+
+/// 아직 `migrate_vaults`를 거치지 않은 (이전 크기의) `amm_info`인지
+pub fn is_legacy_amm_info(data: &[u8]) -> bool {
+    data.len() == AmmInfo::LEGACY_LEN
+}
+
+pub fn decode_amm_config(data: &[u8]) -> anchor_lang::Result<AmmConfig> {
+    AmmConfig::try_deserialize(&mut &data[..])
+}
+
+/// 레지스트리 페이지. 전체 풀 목록은 `pda::pool_registry(0..)`을
+/// `AmmConfig::pool_count`가 덮는 페이지 수만큼 읽어 이어 붙이면 된다.
+pub fn decode_pool_registry(data: &[u8]) -> anchor_lang::Result<PoolRegistry> {
+    PoolRegistry::try_deserialize(&mut &data[..])
+}
@@ -0,0 +1,135 @@
+//! 풀 계정 주소. seed는 `solana_dex`의 `#[account(seeds = ...)]`와 같다.
+//! 풀은 mint 주소 오름차순(coin < pc)으로만 만들 수 있으므로 임의의 쌍은 [`sorted_mints`]로 정렬한다.
+
+use crate::{AmmInfo, PROGRAM_ID};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+/// (coin_mint, pc_mint) 순서로 정렬한 mint 쌍
+pub fn sorted_mints(mint_a: Pubkey, mint_b: Pubkey) -> (Pubkey, Pubkey) {
+    if mint_a < mint_b {
+        (mint_a, mint_b)
+    } else {
+        (mint_b, mint_a)
+    }
+}
+
+/// ammv2의 ProgramData 계정 — `initialize_amm_config`가 업그레이드 권한자를 확인한다
+pub fn program_data() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+pub fn amm_config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"amm_config"], &PROGRAM_ID)
+}
+
+pub fn pool_registry(page_index: u32) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool_registry", &page_index.to_le_bytes()], &PROGRAM_ID)
+}
+
+pub fn amm_info(coin_mint: &Pubkey, pc_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"amm_info", coin_mint.as_ref(), pc_mint.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn amm_authority(coin_mint: &Pubkey, pc_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"amm_authority", coin_mint.as_ref(), pc_mint.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn coin_vault(amm_info: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"coin_vault", amm_info.as_ref()], &PROGRAM_ID)
+}
+
+pub fn pc_vault(amm_info: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pc_vault", amm_info.as_ref()], &PROGRAM_ID)
+}
+
+/// `migrate_vaults` 이전의 mint 단위 vault
+pub fn legacy_vault(seed: &[u8], mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seed, mint.as_ref()], &PROGRAM_ID)
+}
+
+pub fn lp_mint(coin_mint: &Pubkey, pc_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"lp_mint", coin_mint.as_ref(), pc_mint.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn locked_lp_vault(lp_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"locked_lp", lp_mint.as_ref()], &PROGRAM_ID)
+}
+
+/// 풀 하나의 instruction에 필요한 프로그램 소유 계정 모음
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolKeys {
+    pub amm_info: Pubkey,
+    pub amm_authority: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub locked_lp_vault: Pubkey,
+    // coin/pc/LP가 공유하는 token program (SPL Token 또는 Token-2022)
+    pub token_program: Pubkey,
+}
+
+impl PoolKeys {
+    /// mint 쌍에서 모든 주소를 유도한다 (풀 단위 vault 기준)
+    pub fn derive(coin_mint: Pubkey, pc_mint: Pubkey, token_program: Pubkey) -> Self {
+        let amm_info = amm_info(&coin_mint, &pc_mint).0;
+        let lp_mint = lp_mint(&coin_mint, &pc_mint).0;
+        Self {
+            amm_info,
+            amm_authority: amm_authority(&coin_mint, &pc_mint).0,
+            coin_mint,
+            pc_mint,
+            coin_vault: coin_vault(&amm_info).0,
+            pc_vault: pc_vault(&amm_info).0,
+            lp_mint,
+            locked_lp_vault: locked_lp_vault(&lp_mint).0,
+            token_program,
+        }
+    }
+
+    /// 조회한 풀 상태로 주소를 만든다. vault는 상태에 기록된 값을 쓰므로 아직
+    /// `migrate_vaults`를 거치지 않은 풀(`decode_amm_info`가 이전 레이아웃도 읽는다)에도 맞다.
+    pub fn from_state(state: &AmmInfo, token_program: Pubkey) -> Self {
+        Self {
+            coin_vault: state.coin_vault,
+            pc_vault: state.pc_vault,
+            ..Self::derive(state.coin_mint, state.pc_mint, token_program)
+        }
+    }
+}
+
+/// 사용자 쪽 토큰 계정. 스왑은 `lp_account`를 쓰지 않는다.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserAccounts {
+    pub authority: Pubkey,
+    pub coin_account: Pubkey,
+    pub pc_account: Pubkey,
+    pub lp_account: Pubkey,
+}
+
+impl UserAccounts {
+    /// `authority`의 associated token account들
+    pub fn associated(authority: Pubkey, pool: &PoolKeys) -> Self {
+        let ata = |mint: &Pubkey| {
+            get_associated_token_address_with_program_id(&authority, mint, &pool.token_program)
+        };
+        Self {
+            authority,
+            coin_account: ata(&pool.coin_mint),
+            pc_account: ata(&pool.pc_mint),
+            lp_account: ata(&pool.lp_mint),
+        }
+    }
+}
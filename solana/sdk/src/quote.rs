@@ -0,0 +1,209 @@
+//! 스왑 견적. 곡선과 수수료 계산은 온체인과 같은 `AmmInfo::swap_amount_out/in`을 쓴다.
+//!
+//! 가격은 `AmmInfo`에 기록된 풀 유동성으로 계산하므로 vault 잔고를 따로 조회할 필요가 없다.
+//! 수량은 모두 vault 기준이다. Token-2022 transfer fee가 있는 mint라면 사용자가 실제로
+//! 내거나 받는 양은 그만큼 달라진다. lot 반올림, `min_size`, 가격 밴드와 가격 영향 한도도
+//! 온체인과 같이 적용하므로 견적이 성공하면 같은 상태에서 스왑도 통과한다.
+
+use crate::{AmmInfo, DexError, SwapDirection};
+use anchor_lang::prelude::*;
+
+/// 슬리피지 한도 분모 (basis points)
+pub const SLIPPAGE_DENOMINATOR: u64 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    // 입력 토큰으로 내는 거래 수수료 (프로토콜 몫 포함)
+    pub fee: u64,
+}
+
+/// 정확한 입력 `amount_in`에 대한 출력 견적. `amount_in`은 입력 쪽 lot으로 내림된다.
+pub fn quote_swap_base_in(
+    pool: &AmmInfo,
+    amount_in: u64,
+    direction: SwapDirection,
+    now: i64,
+) -> Result<SwapQuote> {
+    let (source_lot_size, _) = pool.lot_sizes(direction);
+    let amount_in = amount_in - amount_in % source_lot_size;
+    require!(amount_in > 0, DexError::InvalidAmount);
+
+    let (reserve_in, reserve_out) = oriented_reserves(pool, direction)?;
+    let amount_out = pool.swap_amount_out(amount_in, reserve_in, reserve_out, now)?;
+    check_trade(pool, direction, amount_in, amount_out, now)?;
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee: trade_fee(pool, amount_in),
+    })
+}
+
+/// 정확한 출력 `amount_out`에 필요한 입력 견적. `amount_out`은 출력 쪽 lot으로 올림된다.
+pub fn quote_swap_base_out(
+    pool: &AmmInfo,
+    amount_out: u64,
+    direction: SwapDirection,
+    now: i64,
+) -> Result<SwapQuote> {
+    let (_, destination_lot_size) = pool.lot_sizes(direction);
+    let amount_out = amount_out
+        .div_ceil(destination_lot_size)
+        .checked_mul(destination_lot_size)
+        .ok_or(DexError::MathOverflow)?;
+
+    let (reserve_in, reserve_out) = oriented_reserves(pool, direction)?;
+    let amount_in = pool.swap_amount_in(amount_out, reserve_in, reserve_out, now)?;
+    check_trade(pool, direction, amount_in, amount_out, now)?;
+    Ok(SwapQuote {
+        amount_in,
+        amount_out,
+        fee: trade_fee(pool, amount_in),
+    })
+}
+
+/// `swap_base_in`의 `minimum_amount_out`으로 쓸 값 (내림)
+pub fn minimum_amount_out(amount_out: u64, slippage_bps: u64) -> u64 {
+    let slippage_bps = slippage_bps.min(SLIPPAGE_DENOMINATOR);
+    (amount_out as u128 * (SLIPPAGE_DENOMINATOR - slippage_bps) as u128
+        / SLIPPAGE_DENOMINATOR as u128) as u64
+}
+
+/// `swap_base_out`의 `max_amount_in`으로 쓸 값 (올림, u64 상한)
+pub fn maximum_amount_in(amount_in: u64, slippage_bps: u64) -> u64 {
+    let max = (amount_in as u128 * (SLIPPAGE_DENOMINATOR + slippage_bps) as u128)
+        .div_ceil(SLIPPAGE_DENOMINATOR as u128);
+    u64::try_from(max).unwrap_or(u64::MAX)
+}
+
+fn oriented_reserves(pool: &AmmInfo, direction: SwapDirection) -> Result<(u64, u64)> {
+    require!(pool.pool_status()?.can_swap(), DexError::PoolNotReady);
+    let (coin_reserve, pc_reserve) = pool.reserves();
+    Ok(direction.orient(coin_reserve, pc_reserve))
+}
+
+/// 온체인 `check_trade` + 스왑 반영 후 `check_price_impact`
+fn check_trade(
+    pool: &AmmInfo,
+    direction: SwapDirection,
+    amount_in: u64,
+    amount_out: u64,
+    now: i64,
+) -> Result<()> {
+    let (coin_reserve, pc_reserve) = pool.reserves();
+    pool.check_trade(
+        direction,
+        amount_in,
+        amount_out,
+        coin_reserve,
+        pc_reserve,
+        now,
+    )?;
+    let mut after = pool.clone();
+    after.apply_swap(direction, amount_in, amount_out)?;
+    after.check_price_impact(coin_reserve, pc_reserve, now)
+}
+
+fn trade_fee(pool: &AmmInfo, amount_in: u64) -> u64 {
+    (amount_in as u128 * pool.trade_fee_numerator as u128 / pool.trade_fee_denominator as u128)
+        as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode_amm_info, AmmInfo, PoolStatus};
+    use ammv2::calculate_amount_out;
+
+    fn trading_pool() -> AmmInfo {
+        let mut pool = AmmInfo::try_deserialize_unchecked(&mut &[0u8; AmmInfo::LEN][..]).unwrap();
+        pool.status = PoolStatus::Trading as u64;
+        pool.trade_fee_numerator = 30;
+        pool.trade_fee_denominator = 10_000;
+        pool.pool_coin_amount = 1_000_000;
+        pool.pool_pc_amount = 2_000_000;
+        pool.coin_lot_size = 1;
+        pool.pc_lot_size = 1;
+        pool
+    }
+
+    #[test]
+    fn base_in_matches_program_math() {
+        let pool = trading_pool();
+        let quote = quote_swap_base_in(&pool, 10_000, SwapDirection::CoinToPc, 0).unwrap();
+        let expected = calculate_amount_out(10_000, 1_000_000, 2_000_000, 30, 10_000).unwrap();
+        assert_eq!(quote.amount_out, expected);
+        assert_eq!(quote.fee, 30);
+    }
+
+    #[test]
+    fn base_out_covers_the_requested_output() {
+        let pool = trading_pool();
+        let quote = quote_swap_base_out(&pool, 7_777, SwapDirection::PcToCoin, 0).unwrap();
+        let back = quote_swap_base_in(&pool, quote.amount_in, SwapDirection::PcToCoin, 0).unwrap();
+        assert!(back.amount_out >= 7_777);
+    }
+
+    #[test]
+    fn rejects_pools_that_are_not_trading() {
+        let mut pool = trading_pool();
+        pool.status = PoolStatus::DepositOnly as u64;
+        assert!(quote_swap_base_in(&pool, 10, SwapDirection::CoinToPc, 0).is_err());
+    }
+
+    #[test]
+    fn rounds_to_lot_sizes() {
+        let mut pool = trading_pool();
+        pool.coin_lot_size = 100;
+        pool.pc_lot_size = 1_000;
+
+        let quote = quote_swap_base_in(&pool, 10_099, SwapDirection::CoinToPc, 0).unwrap();
+        assert_eq!(quote.amount_in, 10_000);
+        let quote = quote_swap_base_out(&pool, 7_001, SwapDirection::CoinToPc, 0).unwrap();
+        assert_eq!(quote.amount_out, 8_000);
+        assert!(quote_swap_base_in(&pool, 99, SwapDirection::CoinToPc, 0).is_err());
+    }
+
+    #[test]
+    fn enforces_min_size_and_price_limits() {
+        let mut pool = trading_pool();
+        pool.min_size = 1_000;
+        // 1 coin을 사려면 coin 쪽이 min_size 미만
+        assert!(quote_swap_base_out(&pool, 999, SwapDirection::PcToCoin, 0).is_err());
+        assert!(quote_swap_base_in(&pool, 1_000, SwapDirection::CoinToPc, 0).is_ok());
+
+        // 체결 가격이 스왑 전 가격의 99% 미만이면 거부 (30bps 수수료 + 가격 영향)
+        pool.min_price_multiplier = 9_900;
+        assert!(quote_swap_base_in(&pool, 5_000, SwapDirection::CoinToPc, 0).is_ok());
+        assert!(quote_swap_base_in(&pool, 20_000, SwapDirection::CoinToPc, 0).is_err());
+
+        // 스왑 후 가격 변동 1% 한도
+        pool.min_price_multiplier = 0;
+        pool.vol_max_cut_ratio = 100;
+        assert!(quote_swap_base_in(&pool, 4_000, SwapDirection::PcToCoin, 0).is_ok());
+        assert!(quote_swap_base_in(&pool, 40_000, SwapDirection::PcToCoin, 0).is_err());
+    }
+
+    #[test]
+    fn slippage_bounds() {
+        assert_eq!(minimum_amount_out(10_000, 50), 9_950);
+        assert_eq!(maximum_amount_in(10_001, 50), 10_052);
+        assert_eq!(maximum_amount_in(u64::MAX, 50), u64::MAX);
+    }
+
+    #[test]
+    fn decodes_serialized_state() {
+        let mut pool = trading_pool();
+        pool.pool_lp_amount = 42;
+        let mut data = Vec::new();
+        anchor_lang::AccountSerialize::try_serialize(&pool, &mut data).unwrap();
+        assert_eq!(decode_amm_info(&data).unwrap().pool_lp_amount, 42);
+        assert!(decode_amm_info(&data[1..]).is_err());
+
+        // 이전 레이아웃: 앞부분만 있고 새 필드는 0
+        let legacy = decode_amm_info(&data[..AmmInfo::LEGACY_LEN]).unwrap();
+        assert_eq!(legacy.pool_lp_amount, 42);
+        assert_eq!(legacy.trade_fee_numerator, 0);
+    }
+}
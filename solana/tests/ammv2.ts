@@ -0,0 +1,1361 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Ammv2 } from "../target/types/ammv2";
+import {
+    createAccount,
+    createInitializeMintInstruction,
+    createInitializeTransferFeeConfigInstruction,
+    createMint,
+    ExtensionType,
+    getAccount,
+    getMint,
+    getMintLen,
+    mintTo,
+    transfer,
+    TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+const MINIMUM_LIQUIDITY = 1_000;
+
+describe("ammv2", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    const program = anchor.workspace.Ammv2 as Program<Ammv2>;
+
+    const provider = anchor.getProvider() as anchor.AnchorProvider;
+    const payer = (provider.wallet as anchor.Wallet).payer;
+
+    const pda = (seeds: Buffer[]) => anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+    // 풀 관련 PDA 계산 (주어진 mint 순서 그대로)
+    const poolAddresses = (
+        coinMint: anchor.web3.PublicKey,
+        pcMint: anchor.web3.PublicKey,
+        tokenProgram = TOKEN_PROGRAM_ID,
+    ) => {
+        const ammInfo = pda([Buffer.from("amm_info"), coinMint.toBuffer(), pcMint.toBuffer()]);
+        const lpMint = pda([Buffer.from("lp_mint"), coinMint.toBuffer(), pcMint.toBuffer()]);
+        return {
+            ammInfo,
+            ammAuthority: pda([Buffer.from("amm_authority"), coinMint.toBuffer(), pcMint.toBuffer()]),
+            coinMint,
+            pcMint,
+            coinVault: pda([Buffer.from("coin_vault"), ammInfo.toBuffer()]),
+            pcVault: pda([Buffer.from("pc_vault"), ammInfo.toBuffer()]),
+            lpMint,
+            lockedLpVault: pda([Buffer.from("locked_lp"), lpMint.toBuffer()]),
+            tokenProgram,
+        };
+    };
+    type Pool = ReturnType<typeof poolAddresses>;
+
+    // 풀은 mint 주소 오름차순(coin < pc)으로만 만들 수 있다
+    const findPool = (mintA: anchor.web3.PublicKey, mintB: anchor.web3.PublicKey, tokenProgram = TOKEN_PROGRAM_ID) =>
+        Buffer.compare(mintA.toBuffer(), mintB.toBuffer()) < 0
+            ? poolAddresses(mintA, mintB, tokenProgram)
+            : poolAddresses(mintB, mintA, tokenProgram);
+
+    // 전역 설정은 프로그램에 하나 — 다른 테스트 파일이 먼저 만들었을 수 있다
+    const ammConfig = pda([Buffer.from("amm_config")]);
+    const defaultConfig = {
+        feeReceiver: payer.publicKey,
+        defaultTradeFeeBps: new BN(30),
+        defaultProtocolFeeNumerator: new BN(0),
+        creationFee: new BN(0),
+    };
+    // 업그레이드 권한자 확인용 ProgramData 계정
+    const programData = anchor.web3.PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111"),
+    )[0];
+    before(async () => {
+        if (!(await program.account.ammConfig.fetchNullable(ammConfig))) {
+            // 업그레이드 권한자가 아니면 config admin을 차지할 수 없다
+            const squatter = anchor.web3.Keypair.generate();
+            await provider.connection.confirmTransaction(
+                await provider.connection.requestAirdrop(squatter.publicKey, anchor.web3.LAMPORTS_PER_SOL),
+            );
+            await expectError(
+                program.methods
+                    .initializeAmmConfig({ ...defaultConfig, feeReceiver: squatter.publicKey })
+                    .accountsPartial({ ammConfig, admin: squatter.publicKey, programData })
+                    .signers([squatter])
+                    .rpc(),
+                "Unauthorized",
+            );
+
+            await program.methods
+                .initializeAmmConfig(defaultConfig)
+                .accountsPartial({ ammConfig, admin: payer.publicKey, programData })
+                .rpc();
+        }
+    });
+
+    const registryPage = (index: number) => {
+        const page = Buffer.alloc(4);
+        page.writeUInt32LE(index);
+        return pda([Buffer.from("pool_registry"), page]);
+    };
+    // initialize_pool의 팩토리 계정: 설정, 현재 레지스트리 페이지, 생성 수수료 수령 계정
+    const factoryAccounts = async () => {
+        const config = await program.account.ammConfig.fetch(ammConfig);
+        return {
+            ammConfig,
+            poolRegistry: registryPage(Math.floor(config.poolCount.toNumber() / 64)),
+            feeReceiver: config.feeReceiver,
+        };
+    };
+
+    // 사용자: coin/pc/lp 토큰 계정 + 초기 잔고
+    const createUser = async (pool: Pool, amount: number) => {
+        const user = anchor.web3.Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(user.publicKey, anchor.web3.LAMPORTS_PER_SOL),
+        );
+        const account = (mint: anchor.web3.PublicKey) =>
+            createAccount(provider.connection, payer, mint, user.publicKey, undefined, undefined, pool.tokenProgram);
+        const coin = await account(pool.coinMint);
+        const pc = await account(pool.pcMint);
+        const lp = await account(pool.lpMint);
+        await mintTo(provider.connection, payer, pool.coinMint, coin, payer, amount, [], undefined, pool.tokenProgram);
+        await mintTo(provider.connection, payer, pool.pcMint, pc, payer, amount, [], undefined, pool.tokenProgram);
+        return { keypair: user, coin, pc, lp };
+    };
+    type User = Awaited<ReturnType<typeof createUser>>;
+
+    const newMint = () => createMint(provider.connection, payer, payer.publicKey, null, 6);
+
+    const constantProduct = { constantProduct: {} };
+    const stableSwap = { stableSwap: {} };
+
+    const createPool = async (
+        feeBps = 30,
+        coinMint?: anchor.web3.PublicKey,
+        pcMint?: anchor.web3.PublicKey,
+        curveType: typeof constantProduct | typeof stableSwap = constantProduct,
+        amp = 0,
+        tokenProgram = TOKEN_PROGRAM_ID,
+    ) => {
+        const pool = findPool(coinMint ?? (await newMint()), pcMint ?? (await newMint()), tokenProgram);
+
+        await program.methods
+            .initializePool(new BN(0), new BN(feeBps), curveType, new BN(amp))
+            .accountsPartial({ ...pool, ...(await factoryAccounts()), payer: payer.publicKey })
+            .rpc();
+        return pool;
+    };
+
+    const deposit = (
+        pool: Pool,
+        user: User,
+        maxCoin: number,
+        maxPc: number,
+        baseSide = 0,
+        deadline: BN | null = null,
+    ) =>
+        program.methods
+            .deposit(new BN(maxCoin), new BN(maxPc), new BN(baseSide), deadline)
+            .accountsPartial({
+                ...pool,
+                userCoinAccount: user.coin,
+                userPcAccount: user.pc,
+                userLpAccount: user.lp,
+                userAuthority: user.keypair.publicKey,
+            })
+            .signers([user.keypair])
+            .rpc();
+
+    const withdraw = (pool: Pool, user: User, amount: number, minCoin = 0, minPc = 0) =>
+        program.methods
+            .withdraw(new BN(amount), new BN(minCoin), new BN(minPc), null)
+            .accountsPartial({
+                ...pool,
+                userCoinAccount: user.coin,
+                userPcAccount: user.pc,
+                userLpAccount: user.lp,
+                userAuthority: user.keypair.publicKey,
+            })
+            .signers([user.keypair])
+            .rpc();
+
+    const openTrading = (pool: Pool) =>
+        program.methods
+            .setPoolStatus({ trading: {} })
+            .accountsPartial({ ammInfo: pool.ammInfo, admin: payer.publicKey })
+            .rpc();
+
+    const coinToPc = { coinToPc: {} };
+    const pcToCoin = { pcToCoin: {} };
+
+    const swapBaseIn = (
+        pool: Pool,
+        user: User,
+        amountIn: number,
+        minimumAmountOut: number,
+        direction: typeof coinToPc | typeof pcToCoin = coinToPc,
+        accounts: Record<string, anchor.web3.PublicKey> = {},
+        deadline: BN | null = null,
+    ) =>
+        program.methods
+            .swapBaseIn(new BN(amountIn), new BN(minimumAmountOut), direction, deadline)
+            .accountsPartial({
+                ...pool,
+                userCoinAccount: user.coin,
+                userPcAccount: user.pc,
+                userAuthority: user.keypair.publicKey,
+                ...accounts,
+            })
+            .signers([user.keypair])
+            .rpc();
+
+    // 실패해야 하는 호출의 Anchor 에러 코드 확인
+    const expectError = async (promise: Promise<unknown>, code: string) => {
+        try {
+            await promise;
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal(code);
+            return;
+        }
+        expect.fail(`expected ${code}`);
+    };
+
+    const balance = async (account: anchor.web3.PublicKey, tokenProgram = TOKEN_PROGRAM_ID) =>
+        Number((await getAccount(provider.connection, account, undefined, tokenProgram)).amount);
+
+    describe("pool factory", () => {
+        const updateConfig = (config: typeof defaultConfig, admin = payer) =>
+            program.methods
+                .updateAmmConfig(config)
+                .accountsPartial({ ammConfig, admin: admin.publicKey })
+                .signers(admin === payer ? [] : [admin])
+                .rpc();
+
+        afterEach(async () => {
+            await updateConfig(defaultConfig);
+        });
+
+        it("rejects a pool whose mints are not in canonical order", async () => {
+            const { coinMint, pcMint } = findPool(await newMint(), await newMint());
+            await expectError(
+                program.methods
+                    .initializePool(new BN(0), new BN(30), constantProduct, new BN(0))
+                    .accountsPartial({
+                        ...poolAddresses(pcMint, coinMint),
+                        ...(await factoryAccounts()),
+                        payer: payer.publicKey,
+                    })
+                    .rpc(),
+                "MintsNotSorted",
+            );
+        });
+
+        it("registers every pool in the paginated registry", async () => {
+            const countBefore = (await program.account.ammConfig.fetch(ammConfig)).poolCount.toNumber();
+            const pool = await createPool();
+
+            const config = await program.account.ammConfig.fetch(ammConfig);
+            expect(config.poolCount.toNumber()).to.equal(countBefore + 1);
+            const page = await program.account.poolRegistry.fetch(registryPage(Math.floor(countBefore / 64)));
+            const entry = page.pools[countBefore % 64];
+            expect(entry.ammInfo.toBase58()).to.equal(pool.ammInfo.toBase58());
+            expect(entry.coinMint.toBase58()).to.equal(pool.coinMint.toBase58());
+            expect(entry.pcMint.toBase58()).to.equal(pool.pcMint.toBase58());
+            expect(entry.lpMint.toBase58()).to.equal(pool.lpMint.toBase58());
+        });
+
+        it("applies config defaults and charges the creation fee", async () => {
+            const feeReceiver = anchor.web3.Keypair.generate().publicKey;
+            await updateConfig({
+                feeReceiver,
+                defaultTradeFeeBps: new BN(100),
+                defaultProtocolFeeNumerator: new BN(2_000),
+                creationFee: new BN(anchor.web3.LAMPORTS_PER_SOL / 10),
+            });
+
+            const pool = findPool(await newMint(), await newMint());
+            await program.methods
+                .initializePool(new BN(0), null, constantProduct, new BN(0))
+                .accountsPartial({ ...pool, ...(await factoryAccounts()), payer: payer.publicKey })
+                .rpc();
+
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.tradeFeeNumerator.toNumber()).to.equal(100);
+            expect(ammInfo.protocolFeeNumerator.toNumber()).to.equal(2_000);
+            expect(ammInfo.admin.toBase58()).to.equal(payer.publicKey.toBase58());
+            expect(await provider.connection.getBalance(feeReceiver)).to.equal(anchor.web3.LAMPORTS_PER_SOL / 10);
+        });
+
+        it("only the config admin can update it", async () => {
+            const other = anchor.web3.Keypair.generate();
+            await expectError(updateConfig(defaultConfig, other), "Unauthorized");
+            await expectError(updateConfig({ ...defaultConfig, defaultTradeFeeBps: new BN(7) }), "InvalidFeeTier");
+        });
+    });
+
+    describe("per-pool vaults", () => {
+        it("creates many pools that share a base token", async () => {
+            const base = await newMint();
+            const pools: Pool[] = [];
+            // base는 상대 mint 주소에 따라 coin 또는 pc가 된다
+            for (let i = 0; i < 5; i++) {
+                pools.push(await createPool(30, base));
+            }
+
+            const vaults = pools.flatMap((pool) => [pool.coinVault.toBase58(), pool.pcVault.toBase58()]);
+            expect(new Set(vaults).size).to.equal(vaults.length);
+
+            // 각 풀의 유동성은 서로 섞이지 않는다
+            for (const [i, pool] of pools.entries()) {
+                const user = await createUser(pool, 10_000_000);
+                await deposit(pool, user, 100_000 * (i + 1), 100_000 * (i + 1));
+            }
+            for (const [i, pool] of pools.entries()) {
+                const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+                expect(ammInfo.coinVault.toBase58()).to.equal(pool.coinVault.toBase58());
+                expect(ammInfo.pcVault.toBase58()).to.equal(pool.pcVault.toBase58());
+                expect(await balance(pool.coinVault)).to.equal(100_000 * (i + 1));
+                expect(await balance(pool.pcVault)).to.equal(100_000 * (i + 1));
+            }
+        });
+
+        // tests/fixtures/legacy_pool: 이전 버전이 만든 368바이트 AmmInfo + mint 단위 vault
+        // (sdk/examples/legacy_pool_fixtures.rs로 생성, Anchor.toml이 validator에 불러온다)
+        const legacyPool = poolAddresses(
+            new anchor.web3.PublicKey("5ffaZEuyVkozN9VZiuAmyoFwnjG2Fz4f51yxtmTfvDBD"),
+            new anchor.web3.PublicKey("HPW218tYxyiZtyttU732WQbQNTGUHo3Mc22WHnuggrzJ"),
+        );
+        const legacyVaults = (pool: Pool) => ({
+            legacyCoinVault: pda([Buffer.from("coin_vault"), pool.coinMint.toBuffer()]),
+            legacyPcVault: pda([Buffer.from("pc_vault"), pool.pcMint.toBuffer()]),
+        });
+        const migrateVaults = async (pool: Pool, admin = payer) => {
+            const { poolRegistry } = await factoryAccounts();
+            return program.methods
+                .migrateVaults()
+                .accountsPartial({ ...pool, ...legacyVaults(pool), ammConfig, poolRegistry, admin: admin.publicKey })
+                .signers(admin === payer ? [] : [admin])
+                .rpc();
+        };
+
+        it("migrates a legacy-layout pool to per-pool vaults", async () => {
+            const { legacyCoinVault, legacyPcVault } = legacyVaults(legacyPool);
+            expect((await provider.connection.getAccountInfo(legacyPool.ammInfo)).data.length).to.equal(368);
+            const coinAmount = await balance(legacyCoinVault);
+            const pcAmount = await balance(legacyPcVault);
+
+            // 이전 풀에는 admin이 없다 — config admin만 옮길 수 있다
+            const other = anchor.web3.Keypair.generate();
+            await provider.connection.confirmTransaction(
+                await provider.connection.requestAirdrop(other.publicKey, anchor.web3.LAMPORTS_PER_SOL),
+            );
+            await expectError(migrateVaults(legacyPool, other), "Unauthorized");
+
+            const countBefore = (await program.account.ammConfig.fetch(ammConfig)).poolCount.toNumber();
+            await migrateVaults(legacyPool);
+
+            const ammInfo = await program.account.ammInfo.fetch(legacyPool.ammInfo);
+            expect(ammInfo.coinVault.toBase58()).to.equal(legacyPool.coinVault.toBase58());
+            expect(ammInfo.pcVault.toBase58()).to.equal(legacyPool.pcVault.toBase58());
+            expect(await balance(legacyPool.coinVault)).to.equal(coinAmount);
+            expect(await balance(legacyPool.pcVault)).to.equal(pcAmount);
+            expect(await provider.connection.getAccountInfo(legacyCoinVault)).to.be.null;
+            expect(await provider.connection.getAccountInfo(legacyPcVault)).to.be.null;
+
+            // 이전 기록(공급 시점)이 아니라 옮긴 vault 잔고가 유동성이 된다
+            expect(ammInfo.poolCoinAmount.toNumber()).to.equal(coinAmount);
+            expect(ammInfo.poolPcAmount.toNumber()).to.equal(pcAmount);
+            expect(ammInfo.admin.toBase58()).to.equal(payer.publicKey.toBase58());
+            expect(ammInfo.status.toNumber()).to.equal(1);
+            expect(ammInfo.tradeFeeNumerator.toNumber()).to.equal(25);
+
+            const page = await program.account.poolRegistry.fetch(registryPage(Math.floor(countBefore / 64)));
+            expect(page.pools[countBefore % 64].ammInfo.toBase58()).to.equal(legacyPool.ammInfo.toBase58());
+
+            // 옮긴 뒤에는 admin이 거래를 열 수 있다
+            await program.methods
+                .setPoolStatus({ trading: {} })
+                .accountsPartial({ ammInfo: legacyPool.ammInfo, admin: payer.publicKey })
+                .rpc();
+        });
+
+        it("rejects migrate_vaults on a pool that already uses per-pool vaults", async () => {
+            // 이전 seeds의 vault가 존재하지 않음 (이미 옮긴 풀도 같다)
+            await expectError(migrateVaults(await createPool()), "AccountNotInitialized");
+            await expectError(migrateVaults(legacyPool), "AccountNotInitialized");
+        });
+    });
+
+    describe("minimum liquidity", () => {
+        it("locks MINIMUM_LIQUIDITY on the first deposit", async () => {
+            const pool = await createPool();
+            const user = await createUser(pool, 1_000_000);
+
+            await deposit(pool, user, 100_000, 100_000);
+
+            expect(await balance(pool.lockedLpVault)).to.equal(MINIMUM_LIQUIDITY);
+            expect(await balance(user.lp)).to.equal(100_000 - MINIMUM_LIQUIDITY);
+            const lpMint = await getMint(provider.connection, pool.lpMint);
+            expect(Number(lpMint.supply)).to.equal(100_000);
+        });
+
+        it("rejects a first deposit that does not exceed MINIMUM_LIQUIDITY", async () => {
+            const pool = await createPool();
+            const user = await createUser(pool, 1_000_000);
+
+            try {
+                await deposit(pool, user, MINIMUM_LIQUIDITY, MINIMUM_LIQUIDITY);
+                expect.fail("deposit should have failed");
+            } catch (err) {
+                expect(err.error.errorCode.code).to.equal("InsufficientInitialLiquidity");
+            }
+        });
+
+        it("a donation to the vaults no longer steals the next depositor's funds", async () => {
+            const pool = await createPool();
+            const attacker = await createUser(pool, 10_000_000);
+            const victim = await createUser(pool, 10_000_000);
+
+            // 공격자: 최소 공급 후 vault에 직접 대량 기부해서 LP 1개의 가치를 부풀림
+            await deposit(pool, attacker, MINIMUM_LIQUIDITY + 1, MINIMUM_LIQUIDITY + 1);
+            expect(await balance(attacker.lp)).to.equal(1);
+            await transfer(provider.connection, payer, attacker.coin, pool.coinVault, attacker.keypair, 1_000_000);
+            await transfer(provider.connection, payer, attacker.pc, pool.pcVault, attacker.keypair, 1_000_000);
+
+            // 피해자: 잠긴 LP 덕분에 0이 아닌 LP를 받는다
+            await deposit(pool, victim, 500_000, 10_000_000);
+            const victimLp = await balance(victim.lp);
+            expect(victimLp).to.be.greaterThan(0);
+
+            // 바로 인출해도 원금의 99% 이상 회수
+            await withdraw(pool, victim, victimLp);
+            const coinLoss = 10_000_000 - (await balance(victim.coin));
+            expect(coinLoss).to.be.lessThan(500_000 / 100);
+        });
+    });
+
+    describe("account binding", () => {
+        let pool: Pool;
+        let other: Pool;
+        let user: User;
+        let attacker: User;
+
+        before(async () => {
+            pool = await createPool();
+            other = await createPool();
+            user = await createUser(pool, 10_000_000);
+            attacker = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        const depositWith = (accounts: Record<string, anchor.web3.PublicKey>) =>
+            program.methods
+                .deposit(new BN(1_000), new BN(1_000), new BN(0), null)
+                .accountsPartial({
+                    ...pool,
+                    userCoinAccount: attacker.coin,
+                    userPcAccount: attacker.pc,
+                    userLpAccount: attacker.lp,
+                    userAuthority: attacker.keypair.publicKey,
+                    ...accounts,
+                })
+                .signers([attacker.keypair])
+                .rpc();
+
+        it("rejects a fake coin vault", async () => {
+            await expectError(depositWith({ coinVault: attacker.coin }), "InvalidCoinVault");
+        });
+
+        it("rejects a fake pc vault", async () => {
+            await expectError(depositWith({ pcVault: attacker.pc }), "InvalidPcVault");
+        });
+
+        it("rejects another pool's LP mint", async () => {
+            await expectError(
+                depositWith({ lpMint: other.lpMint, lockedLpVault: other.lockedLpVault }),
+                "InvalidLpMint",
+            );
+        });
+
+        it("rejects a user token account with the wrong mint", async () => {
+            await expectError(depositWith({ userCoinAccount: attacker.pc }), "InvalidUserTokenMint");
+        });
+
+        it("rejects paying from a token account the signer does not own", async () => {
+            await expectError(depositWith({ userCoinAccount: user.coin }), "InvalidUserTokenOwner");
+        });
+
+        it("rejects withdraw against another pool's amm_info", async () => {
+            await expectError(
+                program.methods
+                    .withdraw(new BN(1), new BN(0), new BN(0), null)
+                    .accountsPartial({
+                        ...pool,
+                        ammInfo: other.ammInfo,
+                        userCoinAccount: user.coin,
+                        userPcAccount: user.pc,
+                        userLpAccount: user.lp,
+                        userAuthority: user.keypair.publicKey,
+                    })
+                    .signers([user.keypair])
+                    .rpc(),
+                "ConstraintSeeds",
+            );
+        });
+
+        it("rejects a swap into a foreign vault", async () => {
+            await expectError(
+                swapBaseIn(pool, attacker, 1_000, 0, coinToPc, { pcVault: attacker.pc }),
+                "InvalidPcVault",
+            );
+        });
+
+        it("rejects a swap with the same vault on both sides", async () => {
+            await expectError(
+                swapBaseIn(pool, attacker, 1_000, 0, coinToPc, { pcVault: pool.coinVault }),
+                "InvalidPcVault",
+            );
+        });
+
+        it("rejects a swap paid from someone else's account", async () => {
+            await expectError(
+                swapBaseIn(pool, attacker, 1_000, 0, pcToCoin, { userPcAccount: user.pc }),
+                "InvalidUserTokenOwner",
+            );
+        });
+    });
+
+    describe("swap direction", () => {
+        it("swaps both ways and keeps pool reserves in sync with the vaults", async () => {
+            const pool = await createPool();
+            const user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+
+            await swapBaseIn(pool, user, 10_000, 1, coinToPc);
+            await swapBaseIn(pool, user, 25_000, 1, pcToCoin);
+
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.poolCoinAmount.toNumber()).to.equal(await balance(pool.coinVault));
+            expect(ammInfo.poolPcAmount.toNumber()).to.equal(await balance(pool.pcVault));
+        });
+    });
+
+    describe("fee tiers", () => {
+        it("rejects a fee outside the tiers", async () => {
+            await expectError(createPool(25), "InvalidFeeTier");
+        });
+
+        it("charges the pool's own tier", async () => {
+            // 10_000 입력, 1_000_000 / 1_000_000 풀: 1 bps → 9_900, 100 bps → 9_802
+            for (const [feeBps, expected] of [
+                [1, 9_900],
+                [100, 9_802],
+            ]) {
+                const pool = await createPool(feeBps);
+                const user = await createUser(pool, 10_000_000);
+                await deposit(pool, user, 1_000_000, 1_000_000);
+                await openTrading(pool);
+
+                const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+                expect(ammInfo.tradeFeeNumerator.toNumber()).to.equal(feeBps);
+                expect(ammInfo.tradeFeeDenominator.toNumber()).to.equal(10_000);
+
+                const before = await balance(user.pc);
+                await swapBaseIn(pool, user, 10_000, 1, coinToPc);
+                expect((await balance(user.pc)) - before).to.equal(expected);
+            }
+        });
+    });
+
+    describe("pool status", () => {
+        let pool: Pool;
+        let user: User;
+
+        const setStatus = (status: object, admin = payer) =>
+            program.methods
+                .setPoolStatus(status)
+                .accountsPartial({ ammInfo: pool.ammInfo, admin: admin.publicKey })
+                .signers(admin === payer ? [] : [admin])
+                .rpc();
+
+        before(async () => {
+            pool = await createPool();
+            user = await createUser(pool, 10_000_000);
+        });
+
+        it("starts deposit-only and rejects swaps", async () => {
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.status.toNumber()).to.equal(1);
+
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await expectError(swapBaseIn(pool, user, 1_000, 1, coinToPc), "PoolNotReady");
+        });
+
+        it("only the admin can change the status", async () => {
+            await expectError(setStatus({ trading: {} }, user.keypair), "Unauthorized");
+            await setStatus({ trading: {} });
+            await swapBaseIn(pool, user, 1_000, 1, coinToPc);
+        });
+
+        it("withdraw-only still lets LPs exit", async () => {
+            await setStatus({ withdrawOnly: {} });
+            await expectError(deposit(pool, user, 1_000, 1_000), "PoolNotReady");
+            await expectError(swapBaseIn(pool, user, 1_000, 1, coinToPc), "PoolNotReady");
+            await withdraw(pool, user, 1_000);
+        });
+
+        it("disabled is terminal", async () => {
+            await expectError(setStatus({ withdrawOnly: {} }), "InvalidStatusTransition");
+            await setStatus({ disabled: {} });
+            await expectError(withdraw(pool, user, 1_000), "PoolNotReady");
+            await expectError(setStatus({ trading: {} }), "InvalidStatusTransition");
+        });
+    });
+
+    describe("admin transfer", () => {
+        let pool: Pool;
+        let newAdmin: anchor.web3.Keypair;
+
+        const propose = (candidate: anchor.web3.PublicKey, admin = payer) =>
+            program.methods
+                .proposeAdmin(candidate)
+                .accountsPartial({ ammInfo: pool.ammInfo, admin: admin.publicKey })
+                .signers(admin === payer ? [] : [admin])
+                .rpc();
+        const accept = (signer: anchor.web3.Keypair) =>
+            program.methods
+                .acceptAdmin()
+                .accountsPartial({ ammInfo: pool.ammInfo, pendingAdmin: signer.publicKey })
+                .signers([signer])
+                .rpc();
+
+        before(async () => {
+            pool = await createPool();
+            newAdmin = anchor.web3.Keypair.generate();
+        });
+
+        it("only the admin can propose", async () => {
+            await expectError(propose(newAdmin.publicKey, newAdmin), "Unauthorized");
+        });
+
+        it("can be cancelled before it is accepted", async () => {
+            await propose(newAdmin.publicKey);
+            await propose(anchor.web3.PublicKey.default);
+            await expectError(accept(newAdmin), "Unauthorized");
+        });
+
+        it("completes only when the candidate signs", async () => {
+            await propose(newAdmin.publicKey);
+            await expectError(accept(anchor.web3.Keypair.generate()), "Unauthorized");
+
+            // 수락 전까지는 기존 admin이 유지된다
+            let ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.admin.toBase58()).to.equal(payer.publicKey.toBase58());
+
+            await accept(newAdmin);
+            ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.admin.toBase58()).to.equal(newAdmin.publicKey.toBase58());
+            expect(ammInfo.pendingAdmin.toBase58()).to.equal(anchor.web3.PublicKey.default.toBase58());
+
+            // 이전 admin은 더 이상 권한이 없다
+            await expectError(openTrading(pool), "Unauthorized");
+            await expectError(propose(payer.publicKey), "Unauthorized");
+        });
+    });
+
+    describe("price oracle", () => {
+        const Q64 = new BN(1).shln(64);
+        const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+        const observe = (pool: Pool) =>
+            program.methods
+                .observe()
+                .accountsPartial({ ammInfo: pool.ammInfo, coinVault: pool.coinVault, pcVault: pool.pcVault })
+                .view();
+
+        let pool: Pool;
+        let user: User;
+
+        before(async () => {
+            pool = await createPool();
+            user = await createUser(pool, 10_000_000);
+            // coin 가격 = pc/coin = 2
+            await deposit(pool, user, 1_000_000, 2_000_000);
+            await openTrading(pool);
+        });
+
+        it("reports the spot price as the TWAP of an unchanged pool", async () => {
+            const first = await observe(pool);
+            await sleep(2_000);
+            const second = await observe(pool);
+
+            const elapsed = second.timestamp.sub(first.timestamp);
+            expect(elapsed.toNumber()).to.be.greaterThan(0);
+            const coinTwap = second.priceCoinCumulative.sub(first.priceCoinCumulative).div(elapsed);
+            const pcTwap = second.pricePcCumulative.sub(first.pricePcCumulative).div(elapsed);
+            expect(coinTwap.toString()).to.equal(Q64.muln(2).toString());
+            expect(pcTwap.toString()).to.equal(Q64.divn(2).toString());
+        });
+
+        it("stores the accumulator when the pool is updated", async () => {
+            const before = await program.account.ammInfo.fetch(pool.ammInfo);
+            await sleep(1_500);
+            await swapBaseIn(pool, user, 1_000, 1, coinToPc);
+
+            // 스왑 직전 가격(2)으로 마지막 갱신 이후 시간만큼 누적
+            const after = await program.account.ammInfo.fetch(pool.ammInfo);
+            const elapsed = after.lastUpdateTimestamp.sub(before.lastUpdateTimestamp);
+            expect(elapsed.toNumber()).to.be.greaterThan(0);
+            expect(after.priceCoinCumulative.sub(before.priceCoinCumulative).toString()).to.equal(
+                Q64.muln(2).mul(elapsed).toString(),
+            );
+        });
+    });
+
+    describe("protocol fee", () => {
+        let pool: Pool;
+        let user: User;
+        let treasury: User;
+
+        const setProtocolFee = (numerator: number, admin = payer) =>
+            program.methods
+                .setProtocolFee(new BN(numerator))
+                .accountsPartial({ ammInfo: pool.ammInfo, admin: admin.publicKey })
+                .signers(admin === payer ? [] : [admin])
+                .rpc();
+        const collect = (admin = payer) =>
+            program.methods
+                .collectProtocolFees()
+                .accountsPartial({
+                    ...pool,
+                    treasuryCoinAccount: treasury.coin,
+                    treasuryPcAccount: treasury.pc,
+                    admin: admin.publicKey,
+                })
+                .signers(admin === payer ? [] : [admin])
+                .rpc();
+
+        before(async () => {
+            pool = await createPool();
+            user = await createUser(pool, 10_000_000);
+            treasury = await createUser(pool, 0);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        it("only the admin can set a valid protocol fee", async () => {
+            await expectError(setProtocolFee(2_000, user.keypair), "Unauthorized");
+            await expectError(setProtocolFee(10_001), "InvalidProtocolFee");
+            await setProtocolFee(2_000);
+        });
+
+        it("accrues its share of the swap fee in the vault", async () => {
+            // 거래 수수료 100_000 * 30 / 10_000 = 300 중 20%
+            await swapBaseIn(pool, user, 100_000, 1, coinToPc);
+
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.protocolFeeCoin.toNumber()).to.equal(60);
+            expect(ammInfo.protocolFeePc.toNumber()).to.equal(0);
+            expect(await balance(pool.coinVault)).to.equal(1_100_000);
+            expect(ammInfo.poolCoinAmount.toNumber()).to.equal(1_100_000 - 60);
+        });
+
+        it("leaves the accrued fee out of the swap price", async () => {
+            // 유동성 1_099_940 / pc 잔고 기준 견적과 같아야 한다
+            const pcVault = await balance(pool.pcVault);
+            const expected = Math.floor((10_000 * 9_970 * pcVault) / (1_099_940 * 10_000 + 10_000 * 9_970));
+            const before = await balance(user.pc);
+            await swapBaseIn(pool, user, 10_000, 1, coinToPc);
+            expect((await balance(user.pc)) - before).to.equal(expected);
+        });
+
+        it("collects the accrued fees to the treasury", async () => {
+            await expectError(collect(user.keypair), "Unauthorized");
+
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            const accrued = ammInfo.protocolFeeCoin.toNumber();
+            const vaultBefore = await balance(pool.coinVault);
+            await collect();
+
+            expect(await balance(treasury.coin)).to.equal(accrued);
+            expect(await balance(pool.coinVault)).to.equal(vaultBefore - accrued);
+            const after = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(after.protocolFeeCoin.toNumber()).to.equal(0);
+            expect(after.poolCoinAmount.toNumber()).to.equal(await balance(pool.coinVault));
+        });
+    });
+
+    describe("swap base out", () => {
+        let pool: Pool;
+        let user: User;
+
+        const swapBaseOut = (amountOut: number, maxAmountIn: number, direction = coinToPc) =>
+            program.methods
+                .swapBaseOut(new BN(amountOut), new BN(maxAmountIn), direction, null)
+                .accountsPartial({
+                    ...pool,
+                    userCoinAccount: user.coin,
+                    userPcAccount: user.pc,
+                    userAuthority: user.keypair.publicKey,
+                })
+                .signers([user.keypair])
+                .rpc();
+
+        beforeEach(async () => {
+            pool = await createPool();
+            user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        it("delivers exactly the requested output for the minimum input", async () => {
+            const coinBefore = await balance(user.coin);
+            const pcBefore = await balance(user.pc);
+
+            // ceil(1_000_000 * 10_000 * 10_000 / (990_000 * 9_970)) = 10_132
+            await swapBaseOut(10_000, 10_132);
+
+            expect((await balance(user.pc)) - pcBefore).to.equal(10_000);
+            expect(coinBefore - (await balance(user.coin))).to.equal(10_132);
+        });
+
+        it("fails when the required input exceeds the maximum", async () => {
+            await expectError(swapBaseOut(10_000, 10_131), "SlippageExceeded");
+        });
+
+        it("cannot drain the output reserve", async () => {
+            await expectError(swapBaseOut(1_000_000, 10_000_000, pcToCoin), "InsufficientLiquidity");
+        });
+    });
+
+    describe("skim / sync", () => {
+        it("ignores donations for pricing until skimmed or synced", async () => {
+            const pool = await createPool();
+            const user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+            await transfer(provider.connection, payer, user.coin, pool.coinVault, user.keypair, 50_000);
+
+            // 기부는 풀 유동성에 잡히지 않는다
+            let ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.poolCoinAmount.toNumber()).to.equal(1_000_000);
+
+            // skim: 잔여분만 recipient로
+            const recipient = await createUser(pool, 0);
+            await program.methods
+                .skim()
+                .accountsPartial({
+                    ...pool,
+                    recipientCoinAccount: recipient.coin,
+                    recipientPcAccount: recipient.pc,
+                })
+                .rpc();
+            expect(await balance(recipient.coin)).to.equal(50_000);
+            expect(await balance(recipient.pc)).to.equal(0);
+            expect(await balance(pool.coinVault)).to.equal(1_000_000);
+
+            // sync: 기부를 풀 유동성으로 흡수
+            await transfer(provider.connection, payer, user.pc, pool.pcVault, user.keypair, 20_000);
+            await program.methods.sync().accountsPartial(pool).rpc();
+            ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.poolCoinAmount.toNumber()).to.equal(1_000_000);
+            expect(ammInfo.poolPcAmount.toNumber()).to.equal(1_020_000);
+        });
+    });
+
+    describe("pool params", () => {
+        const updateParams = (
+            pool: Pool,
+            {
+                coinLotSize = 1,
+                pcLotSize = 1,
+                minSize = 1,
+                minPriceMultiplier = 0,
+                maxPriceMultiplier = 0,
+                volMaxCutRatio = 0,
+            },
+            admin = payer,
+        ) =>
+            program.methods
+                .updatePoolParams({
+                    coinLotSize: new BN(coinLotSize),
+                    pcLotSize: new BN(pcLotSize),
+                    minSize: new BN(minSize),
+                    minPriceMultiplier: new BN(minPriceMultiplier),
+                    maxPriceMultiplier: new BN(maxPriceMultiplier),
+                    volMaxCutRatio: new BN(volMaxCutRatio),
+                })
+                .accountsPartial({ ammInfo: pool.ammInfo, admin: admin.publicKey })
+                .signers(admin === payer ? [] : [admin])
+                .rpc();
+
+        let pool: Pool;
+        let user: User;
+
+        beforeEach(async () => {
+            pool = await createPool();
+            user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        it("only the admin can update params and they must be valid", async () => {
+            await expectError(updateParams(pool, { minSize: 10 }, user.keypair), "Unauthorized");
+            await expectError(updateParams(pool, { coinLotSize: 0 }), "InvalidPoolParams");
+            await expectError(updateParams(pool, { maxPriceMultiplier: 9_000 }), "InvalidPoolParams");
+
+            await updateParams(pool, { minSize: 10, volMaxCutRatio: 500 });
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.minSize.toNumber()).to.equal(10);
+            expect(ammInfo.volMaxCutRatio.toNumber()).to.equal(500);
+        });
+
+        it("rounds the input down to the lot size", async () => {
+            await updateParams(pool, { coinLotSize: 1_000 });
+            const before = await balance(user.coin);
+            await swapBaseIn(pool, user, 12_345, 1, coinToPc);
+            expect(before - (await balance(user.coin))).to.equal(12_000);
+        });
+
+        it("rejects swaps below min_size", async () => {
+            await updateParams(pool, { minSize: 5_000 });
+            await expectError(swapBaseIn(pool, user, 4_999, 1, coinToPc), "BelowMinSize");
+            await swapBaseIn(pool, user, 5_000, 1, coinToPc);
+        });
+
+        it("rejects swaps outside the price band or with too much impact", async () => {
+            // 체결 가격이 스왑 전 가격의 99% 이상이어야 한다
+            await updateParams(pool, { minPriceMultiplier: 9_900 });
+            await expectError(swapBaseIn(pool, user, 20_000, 1, coinToPc), "PriceOutOfBand");
+            await swapBaseIn(pool, user, 2_000, 1, coinToPc);
+
+            // 스왑 한 번에 가격이 1% 넘게 움직이면 거부
+            await updateParams(pool, { volMaxCutRatio: 100 });
+            await expectError(swapBaseIn(pool, user, 20_000, 1, pcToCoin), "PriceImpactTooHigh");
+            await swapBaseIn(pool, user, 2_000, 1, pcToCoin);
+        });
+
+        it("applies the same limits to the internal swap of single-side deposits and withdrawals", async () => {
+            // 100_000 중 절반 가까이가 스왑되므로 1% 한도를 넘는다
+            await updateParams(pool, { volMaxCutRatio: 100 });
+            await expectError(
+                program.methods
+                    .depositSingleSide(new BN(100_000), { coin: {} }, new BN(0), null)
+                    .accountsPartial({
+                        ...pool,
+                        userSourceAccount: user.coin,
+                        userLpAccount: user.lp,
+                        userAuthority: user.keypair.publicKey,
+                    })
+                    .signers([user.keypair])
+                    .rpc(),
+                "PriceImpactTooHigh",
+            );
+            await expectError(
+                program.methods
+                    .withdrawSingleSide(new BN(100_000), { coin: {} }, new BN(0), null)
+                    .accountsPartial({
+                        ...pool,
+                        userCoinAccount: user.coin,
+                        userPcAccount: user.pc,
+                        userLpAccount: user.lp,
+                        userAuthority: user.keypair.publicKey,
+                    })
+                    .signers([user.keypair])
+                    .rpc(),
+                "PriceImpactTooHigh",
+            );
+        });
+    });
+
+    describe("withdraw slippage", () => {
+        let pool: Pool;
+        let user: User;
+
+        beforeEach(async () => {
+            pool = await createPool();
+            user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        it("fails when the payout is below the minimums", async () => {
+            // 출금 직전에 누군가 비율을 기울인 상황
+            await swapBaseIn(pool, user, 200_000, 1, coinToPc);
+            await expectError(withdraw(pool, user, 100_000, 100_000, 100_000), "SlippageExceeded");
+        });
+
+        it("pays out when the minimums are met", async () => {
+            await withdraw(pool, user, 100_000, 99_000, 99_000);
+        });
+
+        it("withdraws a single side with one minimum output", async () => {
+            const before = await balance(user.coin);
+            const pcBefore = await balance(user.pc);
+            await program.methods
+                .withdrawSingleSide(new BN(100_000), { coin: {} }, new BN(185_000), null)
+                .accountsPartial({
+                    ...pool,
+                    userCoinAccount: user.coin,
+                    userPcAccount: user.pc,
+                    userLpAccount: user.lp,
+                    userAuthority: user.keypair.publicKey,
+                })
+                .signers([user.keypair])
+                .rpc();
+
+            expect((await balance(user.coin)) - before).to.be.greaterThan(185_000);
+            expect(await balance(user.pc)).to.equal(pcBefore);
+        });
+
+        it("fails a single-side withdraw below its minimum", async () => {
+            await expectError(
+                program.methods
+                    .withdrawSingleSide(new BN(100_000), { pc: {} }, new BN(200_000), null)
+                    .accountsPartial({
+                        ...pool,
+                        userCoinAccount: user.coin,
+                        userPcAccount: user.pc,
+                        userLpAccount: user.lp,
+                        userAuthority: user.keypair.publicKey,
+                    })
+                    .signers([user.keypair])
+                    .rpc(),
+                "SlippageExceeded",
+            );
+        });
+    });
+
+    describe("single-side deposit", () => {
+        let pool: Pool;
+        let user: User;
+
+        const depositSingleSide = (amountIn: number, side: object, minLpOut: number, source: anchor.web3.PublicKey) =>
+            program.methods
+                .depositSingleSide(new BN(amountIn), side, new BN(minLpOut), null)
+                .accountsPartial({
+                    ...pool,
+                    userSourceAccount: source,
+                    userLpAccount: user.lp,
+                    userAuthority: user.keypair.publicKey,
+                })
+                .signers([user.keypair])
+                .rpc();
+
+        beforeEach(async () => {
+            pool = await createPool();
+            user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        it("mints LP from one token and leaves the stats matching the vaults", async () => {
+            const lpBefore = await balance(user.lp);
+            const pcBefore = await balance(user.pc);
+            await depositSingleSide(100_000, { coin: {} }, 45_000, user.coin);
+
+            // 약 절반이 스왑되므로 양쪽 공급 LP의 절반 남짓
+            const minted = (await balance(user.lp)) - lpBefore;
+            expect(minted).to.be.greaterThan(45_000);
+            expect(minted).to.be.lessThan(50_000);
+            expect(await balance(user.pc)).to.equal(pcBefore);
+
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.poolCoinAmount.toNumber()).to.be.at.most(await balance(pool.coinVault));
+            expect(ammInfo.poolPcAmount.toNumber()).to.be.at.most(await balance(pool.pcVault));
+        });
+
+        it("fails below the minimum LP output", async () => {
+            await expectError(depositSingleSide(100_000, { pc: {} }, 50_000, user.pc), "SlippageExceeded");
+        });
+
+        it("rejects a source account of the other side", async () => {
+            await expectError(depositSingleSide(100_000, { pc: {} }, 0, user.coin), "InvalidUserTokenMint");
+        });
+    });
+
+    describe("events", () => {
+        // 트랜잭션 로그에서 Anchor 이벤트 디코딩
+        const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+        const eventsOf = async (signature: string) => {
+            await provider.connection.confirmTransaction(signature, "confirmed");
+            const tx = await provider.connection.getTransaction(signature, {
+                commitment: "confirmed",
+                maxSupportedTransactionVersion: 0,
+            });
+            return [...parser.parseLogs(tx.meta.logMessages)];
+        };
+        const eventOf = async (signature: string, name: string) => {
+            const event = (await eventsOf(signature)).find((e) => e.name === name);
+            expect(event, name).to.not.be.undefined;
+            return event.data;
+        };
+
+        let pool: Pool;
+        let user: User;
+
+        before(async () => {
+            pool = findPool(await newMint(), await newMint());
+            user = await createUser(pool, 10_000_000);
+        });
+
+        it("emits PoolInitialized", async () => {
+            const signature = await program.methods
+                .initializePool(new BN(0), new BN(30), constantProduct, new BN(0))
+                .accountsPartial({ ...pool, ...(await factoryAccounts()), payer: payer.publicKey })
+                .rpc();
+
+            const event = await eventOf(signature, "poolInitialized");
+            expect(event.ammInfo.toBase58()).to.equal(pool.ammInfo.toBase58());
+            expect(event.lpMint.toBase58()).to.equal(pool.lpMint.toBase58());
+            expect(event.admin.toBase58()).to.equal(payer.publicKey.toBase58());
+            expect(event.tradeFeeNumerator.toNumber()).to.equal(30);
+        });
+
+        it("emits LiquidityAdded with the post-deposit reserves", async () => {
+            const event = await eventOf(await deposit(pool, user, 1_000_000, 1_000_000), "liquidityAdded");
+            expect(event.user.toBase58()).to.equal(user.keypair.publicKey.toBase58());
+            expect(event.coinAmount.toNumber()).to.equal(1_000_000);
+            expect(event.lpAmount.toNumber()).to.equal(1_000_000 - MINIMUM_LIQUIDITY);
+            expect(event.lpSupply.toNumber()).to.equal(1_000_000);
+            expect(event.coinReserve.toNumber()).to.equal(await balance(pool.coinVault));
+            expect(event.pcReserve.toNumber()).to.equal(await balance(pool.pcVault));
+        });
+
+        it("emits Swap with the amounts and post-trade reserves", async () => {
+            await openTrading(pool);
+            const pcBefore = await balance(user.pc);
+            const event = await eventOf(await swapBaseIn(pool, user, 10_000, 1), "swap");
+
+            expect(event.direction).to.deep.equal(coinToPc);
+            expect(event.amountIn.toNumber()).to.equal(10_000);
+            expect(event.amountOut.toNumber()).to.equal((await balance(user.pc)) - pcBefore);
+            expect(event.coinReserve.toNumber()).to.equal(await balance(pool.coinVault));
+            expect(event.pcReserve.toNumber()).to.equal(await balance(pool.pcVault));
+        });
+
+        it("emits LiquidityRemoved", async () => {
+            const coinBefore = await balance(user.coin);
+            const event = await eventOf(await withdraw(pool, user, 100_000), "liquidityRemoved");
+
+            expect(event.lpAmount.toNumber()).to.equal(100_000);
+            expect(event.coinAmount.toNumber()).to.equal((await balance(user.coin)) - coinBefore);
+            expect(event.lpSupply.toNumber()).to.equal(900_000);
+            expect(event.coinReserve.toNumber()).to.equal(await balance(pool.coinVault));
+        });
+    });
+
+    describe("deadline", () => {
+        let pool: Pool;
+        let user: User;
+        const past = () => new BN(Math.floor(Date.now() / 1000) - 600);
+        const future = () => new BN(Math.floor(Date.now() / 1000) + 600);
+
+        before(async () => {
+            pool = await createPool();
+            user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        it("rejects an expired deposit", async () => {
+            await expectError(deposit(pool, user, 1_000, 1_000, 0, past()), "DeadlineExceeded");
+        });
+
+        it("rejects an expired swap", async () => {
+            await expectError(swapBaseIn(pool, user, 1_000, 0, coinToPc, {}, past()), "DeadlineExceeded");
+        });
+
+        it("rejects an expired withdraw", async () => {
+            await expectError(
+                program.methods
+                    .withdraw(new BN(1_000), new BN(0), new BN(0), past())
+                    .accountsPartial({
+                        ...pool,
+                        userCoinAccount: user.coin,
+                        userPcAccount: user.pc,
+                        userLpAccount: user.lp,
+                        userAuthority: user.keypair.publicKey,
+                    })
+                    .signers([user.keypair])
+                    .rpc(),
+                "DeadlineExceeded",
+            );
+        });
+
+        it("accepts operations before the deadline", async () => {
+            await deposit(pool, user, 1_000, 1_000, 0, future());
+            await swapBaseIn(pool, user, 1_000, 0, coinToPc, {}, future());
+        });
+    });
+
+    describe("stable swap", () => {
+        let pool: Pool;
+        let user: User;
+
+        before(async () => {
+            pool = await createPool(1, undefined, undefined, stableSwap, 100);
+            user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        it("mints D as the initial liquidity", async () => {
+            // 균형 잡힌 풀의 D = x + y
+            expect(await balance(user.lp)).to.equal(2_000_000 - MINIMUM_LIQUIDITY);
+        });
+
+        it("trades close to 1:1 around the peg", async () => {
+            const before = await balance(user.pc);
+            await swapBaseIn(pool, user, 10_000, 9_990, coinToPc);
+            // constant product였다면 약 9_900
+            expect((await balance(user.pc)) - before).to.be.greaterThan(9_990);
+        });
+
+        it("quotes exact output with the same curve", async () => {
+            const coinBefore = await balance(user.coin);
+            const pcBefore = await balance(user.pc);
+            await program.methods
+                .swapBaseOut(new BN(10_000), new BN(10_020), pcToCoin, null)
+                .accountsPartial({
+                    ...pool,
+                    userCoinAccount: user.coin,
+                    userPcAccount: user.pc,
+                    userAuthority: user.keypair.publicKey,
+                })
+                .signers([user.keypair])
+                .rpc();
+
+            expect((await balance(user.coin)) - coinBefore).to.equal(10_000);
+            expect(pcBefore - (await balance(user.pc))).to.be.lessThan(10_020);
+        });
+
+        it("rejects an amplification coefficient on a constant product pool", async () => {
+            await expectError(createPool(30, undefined, undefined, constantProduct, 100), "InvalidAmp");
+        });
+
+        it("validates amp ramps", async () => {
+            const now = Math.floor(Date.now() / 1000);
+            const ramp = (target: number, stop: number) =>
+                program.methods
+                    .rampAmp(new BN(target), new BN(stop))
+                    .accountsPartial({ ammInfo: pool.ammInfo, admin: payer.publicKey })
+                    .rpc();
+
+            // 최소 기간 미만, 10배 초과
+            await expectError(ramp(200, now + 60), "InvalidRamp");
+            await expectError(ramp(2_000, now + 2 * 86_400), "InvalidRamp");
+
+            await ramp(200, now + 2 * 86_400);
+            let ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.initialAmp.toNumber()).to.equal(100);
+            expect(ammInfo.targetAmp.toNumber()).to.equal(200);
+
+            await program.methods
+                .stopRampAmp()
+                .accountsPartial({ ammInfo: pool.ammInfo, admin: payer.publicKey })
+                .rpc();
+            ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.targetAmp.toNumber()).to.equal(ammInfo.initialAmp.toNumber());
+        });
+
+        it("does not ramp a constant product pool", async () => {
+            const cpPool = await createPool();
+            await expectError(
+                program.methods
+                    .rampAmp(new BN(10), new BN(Math.floor(Date.now() / 1000) + 2 * 86_400))
+                    .accountsPartial({ ammInfo: cpPool.ammInfo, admin: payer.publicKey })
+                    .rpc(),
+                "InvalidCurveType",
+            );
+        });
+    });
+
+    describe("token-2022 transfer fee", () => {
+        // 1% transfer fee가 걸린 Token-2022 mint
+        const newTransferFeeMint = async () => {
+            const mint = anchor.web3.Keypair.generate();
+            const space = getMintLen([ExtensionType.TransferFeeConfig]);
+            const tx = new anchor.web3.Transaction().add(
+                anchor.web3.SystemProgram.createAccount({
+                    fromPubkey: payer.publicKey,
+                    newAccountPubkey: mint.publicKey,
+                    space,
+                    lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+                    programId: TOKEN_2022_PROGRAM_ID,
+                }),
+                createInitializeTransferFeeConfigInstruction(
+                    mint.publicKey,
+                    payer.publicKey,
+                    payer.publicKey,
+                    100,
+                    BigInt(1_000_000_000),
+                    TOKEN_2022_PROGRAM_ID,
+                ),
+                createInitializeMintInstruction(mint.publicKey, 6, payer.publicKey, null, TOKEN_2022_PROGRAM_ID),
+            );
+            await provider.sendAndConfirm(tx, [mint]);
+            return mint.publicKey;
+        };
+
+        let pool: Pool;
+        let user: User;
+        const vaultBalance = (vault: anchor.web3.PublicKey) => balance(vault, TOKEN_2022_PROGRAM_ID);
+
+        before(async () => {
+            pool = await createPool(
+                30,
+                await newTransferFeeMint(),
+                await newTransferFeeMint(),
+                constantProduct,
+                0,
+                TOKEN_2022_PROGRAM_ID,
+            );
+            user = await createUser(pool, 10_000_000);
+            await deposit(pool, user, 1_000_000, 1_000_000);
+            await openTrading(pool);
+        });
+
+        it("accounts deposits by the amount the vault receives", async () => {
+            expect(await vaultBalance(pool.coinVault)).to.equal(990_000);
+            expect(await vaultBalance(pool.pcVault)).to.equal(990_000);
+
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.poolCoinAmount.toNumber()).to.equal(990_000);
+            expect(ammInfo.poolPcAmount.toNumber()).to.equal(990_000);
+        });
+
+        it("checks the minimum output against the amount the user receives", async () => {
+            // 10_000 보내면 vault에는 9_900 도착 → 9_772 출력, 사용자에게는 9_674 도착
+            await expectError(swapBaseIn(pool, user, 10_000, 9_675), "SlippageExceeded");
+
+            const coinVaultBefore = await vaultBalance(pool.coinVault);
+            const pcBefore = await vaultBalance(user.pc);
+            await swapBaseIn(pool, user, 10_000, 9_674);
+
+            expect((await vaultBalance(pool.coinVault)) - coinVaultBefore).to.equal(9_900);
+            expect((await vaultBalance(user.pc)) - pcBefore).to.equal(9_674);
+
+            const ammInfo = await program.account.ammInfo.fetch(pool.ammInfo);
+            expect(ammInfo.poolCoinAmount.toNumber()).to.equal(await vaultBalance(pool.coinVault));
+            expect(ammInfo.poolPcAmount.toNumber()).to.equal(await vaultBalance(pool.pcVault));
+        });
+
+        it("delivers exactly the requested output on swap_base_out", async () => {
+            const pcBefore = await vaultBalance(user.pc);
+            await program.methods
+                .swapBaseOut(new BN(5_000), new BN(10_000), coinToPc, null)
+                .accountsPartial({
+                    ...pool,
+                    userCoinAccount: user.coin,
+                    userPcAccount: user.pc,
+                    userAuthority: user.keypair.publicKey,
+                })
+                .signers([user.keypair])
+                .rpc();
+
+            expect((await vaultBalance(user.pc)) - pcBefore).to.equal(5_000);
+        });
+    });
+});
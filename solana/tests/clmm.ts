@@ -0,0 +1,257 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Clmm } from "../target/types/clmm";
+import { createAccount, createMint, getAccount, mintTo } from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("clmm", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    const program = anchor.workspace.Clmm as Program<Clmm>;
+
+    const provider = anchor.getProvider() as anchor.AnchorProvider;
+    const payer = (provider.wallet as anchor.Wallet).payer;
+
+    const pda = (seeds: Buffer[]) =>
+        anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const i32 = (value: number) => {
+        const buffer = Buffer.alloc(4);
+        buffer.writeInt32LE(value);
+        return buffer;
+    };
+    const u64 = (value: number) => new BN(value).toArrayLike(Buffer, "le", 8);
+    const sortMints = (mintA: anchor.web3.PublicKey, mintB: anchor.web3.PublicKey) =>
+        Buffer.compare(mintA.toBuffer(), mintB.toBuffer()) < 0 ? [mintA, mintB] : [mintB, mintA];
+    const balance = async (account: anchor.web3.PublicKey) =>
+        Number((await getAccount(provider.connection, account)).amount);
+
+    const expectError = async (promise: Promise<unknown>, code: string) => {
+        try {
+            await promise;
+            expect.fail(`expected ${code}`);
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal(code);
+        }
+    };
+
+    // 가격 1.0 (sqrt = 2^64), 30bps → tick spacing 60, array 하나가 3600 tick
+    const FEE_BPS = 30;
+    const SQRT_PRICE_ONE = new BN("18446744073709551616");
+    const TICK_LOWER = -600;
+    const TICK_UPPER = 600;
+    const LIQUIDITY = new BN("10000000000");
+
+    let pool: Record<string, anchor.web3.PublicKey>;
+    let tickArrayLower: anchor.web3.PublicKey;
+    let tickArrayUpper: anchor.web3.PublicKey;
+    let position: anchor.web3.PublicKey;
+    let userCoin: anchor.web3.PublicKey;
+    let userPc: anchor.web3.PublicKey;
+
+    before(async () => {
+        const [coinMint, pcMint] = sortMints(
+            await createMint(provider.connection, payer, payer.publicKey, null, 6),
+            await createMint(provider.connection, payer, payer.publicKey, null, 6),
+        );
+        const poolState = pda([Buffer.from("pool_state"), coinMint.toBuffer(), pcMint.toBuffer(), u64(FEE_BPS)]);
+        pool = {
+            poolState,
+            ammAuthority: pda([Buffer.from("amm_authority"), poolState.toBuffer()]),
+            coinMint,
+            pcMint,
+            coinVault: pda([Buffer.from("coin_vault"), poolState.toBuffer()]),
+            pcVault: pda([Buffer.from("pc_vault"), poolState.toBuffer()]),
+        };
+        await program.methods
+            .initializePool(new BN(FEE_BPS), SQRT_PRICE_ONE)
+            .accountsPartial({ ...pool, payer: payer.publicKey })
+            .rpc();
+
+        tickArrayLower = pda([Buffer.from("tick_array"), poolState.toBuffer(), i32(-3600)]);
+        tickArrayUpper = pda([Buffer.from("tick_array"), poolState.toBuffer(), i32(0)]);
+        for (const start of [-3600, 0]) {
+            await program.methods
+                .initializeTickArray(start)
+                .accountsPartial({ poolState, payer: payer.publicKey })
+                .rpc();
+        }
+
+        userCoin = await createAccount(provider.connection, payer, coinMint, payer.publicKey);
+        userPc = await createAccount(provider.connection, payer, pcMint, payer.publicKey);
+        await mintTo(provider.connection, payer, coinMint, userCoin, payer, 1_000_000_000);
+        await mintTo(provider.connection, payer, pcMint, userPc, payer, 1_000_000_000);
+
+        position = pda([
+            Buffer.from("position"),
+            poolState.toBuffer(),
+            payer.publicKey.toBuffer(),
+            i32(TICK_LOWER),
+            i32(TICK_UPPER),
+        ]);
+    });
+
+    const liquidityAccounts = () => ({
+        ...pool,
+        position,
+        tickArrayLower,
+        tickArrayUpper,
+        userCoinAccount: userCoin,
+        userPcAccount: userPc,
+        owner: payer.publicKey,
+    });
+
+    const swap = (amountIn: number, direction: object) =>
+        program.methods
+            .swap(new BN(amountIn), new BN(0), new BN(0), direction, null)
+            .accountsPartial({
+                ...pool,
+                userCoinAccount: userCoin,
+                userPcAccount: userPc,
+                userAuthority: payer.publicKey,
+            })
+            // 스왑 방향 순서, 현재 tick을 담은 array부터 (coin → pc는 [0, -3600], 반대는 [-3600, 0])
+            .remainingAccounts(
+                ("coinToPc" in direction ? [tickArrayUpper, tickArrayLower] : [tickArrayLower, tickArrayUpper]).map(
+                    (pubkey) => ({ pubkey, isWritable: true, isSigner: false }),
+                ),
+            )
+            .rpc();
+
+    it("rejects unsupported fee tiers", async () => {
+        const coinMint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+        await expectError(
+            program.methods
+                .initializePool(new BN(25), SQRT_PRICE_ONE)
+                .accountsPartial({
+                    coinMint,
+                    pcMint: pool.pcMint,
+                    payer: payer.publicKey,
+                })
+                .rpc(),
+            "InvalidFeeTier",
+        );
+    });
+
+    it("rejects unsorted mints", async () => {
+        const poolState = pda([
+            Buffer.from("pool_state"),
+            pool.pcMint.toBuffer(),
+            pool.coinMint.toBuffer(),
+            u64(FEE_BPS),
+        ]);
+        await expectError(
+            program.methods
+                .initializePool(new BN(FEE_BPS), SQRT_PRICE_ONE)
+                .accountsPartial({
+                    poolState,
+                    coinMint: pool.pcMint,
+                    pcMint: pool.coinMint,
+                    payer: payer.publicKey,
+                })
+                .rpc(),
+            "MintsNotSorted",
+        );
+    });
+
+    it("creates a pool per fee tier for the same pair", async () => {
+        const poolState = pda([Buffer.from("pool_state"), pool.coinMint.toBuffer(), pool.pcMint.toBuffer(), u64(5)]);
+        const ammAuthority = pda([Buffer.from("amm_authority"), poolState.toBuffer()]);
+        const coinVault = pda([Buffer.from("coin_vault"), poolState.toBuffer()]);
+        await program.methods
+            .initializePool(new BN(5), SQRT_PRICE_ONE)
+            .accountsPartial({
+                poolState,
+                ammAuthority,
+                coinMint: pool.coinMint,
+                pcMint: pool.pcMint,
+                coinVault,
+                pcVault: pda([Buffer.from("pc_vault"), poolState.toBuffer()]),
+                payer: payer.publicKey,
+            })
+            .rpc();
+
+        // 티어마다 vault 권한이 다르다
+        expect(ammAuthority.equals(pool.ammAuthority)).to.be.false;
+        expect((await getAccount(provider.connection, coinVault)).owner.equals(ammAuthority)).to.be.true;
+
+        const state = await program.account.poolState.fetch(poolState);
+        expect(state.tickSpacing).to.equal(10);
+        expect(state.tradeFeeNumerator.toNumber()).to.equal(5);
+    });
+
+    it("opens a position and adds liquidity around the current price", async () => {
+        await program.methods
+            .openPosition(TICK_LOWER, TICK_UPPER)
+            .accountsPartial({ poolState: pool.poolState, owner: payer.publicKey })
+            .rpc();
+
+        await program.methods
+            .increaseLiquidity(LIQUIDITY, new BN(1_000_000_000), new BN(1_000_000_000))
+            .accountsPartial(liquidityAccounts())
+            .rpc();
+
+        // 대칭 구간이므로 양쪽이 거의 같은 양
+        const coin = await balance(pool.coinVault);
+        const pc = await balance(pool.pcVault);
+        expect(coin).to.be.greaterThan(0);
+        expect(Math.abs(coin - pc)).to.be.at.most(1);
+
+        const state = await program.account.poolState.fetch(pool.poolState);
+        expect(state.liquidity.toString()).to.equal(LIQUIDITY.toString());
+    });
+
+    it("rejects ticks that are not aligned to the spacing", async () => {
+        await expectError(
+            program.methods
+                .openPosition(-610, 600)
+                .accountsPartial({ poolState: pool.poolState, owner: payer.publicKey })
+                .rpc(),
+            "InvalidTick",
+        );
+    });
+
+    it("enforces the maximum deposit amounts", async () => {
+        await expectError(
+            program.methods
+                .increaseLiquidity(LIQUIDITY, new BN(1), new BN(1))
+                .accountsPartial(liquidityAccounts())
+                .rpc(),
+            "SlippageExceeded",
+        );
+    });
+
+    it("swaps within the range and accrues fees to the position", async () => {
+        const pcBefore = await balance(userPc);
+        await swap(1_000_000, { coinToPc: {} });
+        const received = (await balance(userPc)) - pcBefore;
+        expect(received).to.be.greaterThan(990_000);
+        expect(received).to.be.lessThan(1_000_000);
+
+        const state = await program.account.poolState.fetch(pool.poolState);
+        expect(state.tickCurrent).to.be.lessThan(0);
+        expect(state.feeGrowthGlobalCoinX64.isZero()).to.be.false;
+
+        await swap(1_000_000, { pcToCoin: {} });
+    });
+
+    it("returns principal and fees on decrease and closes the position", async () => {
+        const coinBefore = await balance(userCoin);
+        await program.methods
+            .decreaseLiquidity(LIQUIDITY, new BN(0), new BN(0))
+            .accountsPartial(liquidityAccounts())
+            .rpc();
+
+        // 두 번의 스왑 수수료(각 3_000)가 포지션에 적립되어 함께 지급
+        expect((await balance(userCoin)) - coinBefore).to.be.greaterThan(0);
+        expect(await balance(pool.coinVault)).to.be.at.most(10);
+        expect(await balance(pool.pcVault)).to.be.at.most(10);
+
+        const state = await program.account.poolState.fetch(pool.poolState);
+        expect(state.liquidity.toNumber()).to.equal(0);
+
+        await program.methods
+            .closePosition()
+            .accountsPartial({ position, owner: payer.publicKey })
+            .rpc();
+        expect(await provider.connection.getAccountInfo(position)).to.be.null;
+    });
+});
@@ -0,0 +1,277 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Ammv2 } from "../target/types/ammv2";
+import { Farm } from "../target/types/farm";
+import {
+    createAccount,
+    createInitializeMintInstruction,
+    createInitializeTransferFeeConfigInstruction,
+    createMint,
+    ExtensionType,
+    getAccount,
+    getMintLen,
+    mintTo,
+    TOKEN_2022_PROGRAM_ID,
+} from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("farm", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    const program = anchor.workspace.Farm as Program<Farm>;
+    const amm = anchor.workspace.Ammv2 as Program<Ammv2>;
+
+    const provider = anchor.getProvider() as anchor.AnchorProvider;
+    const payer = (provider.wallet as anchor.Wallet).payer;
+
+    const pda = (programId: anchor.web3.PublicKey, seeds: Buffer[]) =>
+        anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+    const balance = async (account: anchor.web3.PublicKey) =>
+        Number((await getAccount(provider.connection, account)).amount);
+    const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+    // 전역 설정은 프로그램에 하나 — 없으면 만들고, initialize_pool에 넘길 팩토리 계정을 돌려준다
+    const factoryAccounts = async () => {
+        const ammConfig = pda(amm.programId, [Buffer.from("amm_config")]);
+        // initialize_amm_config는 업그레이드 권한자(로컬 배포자 = payer)만 호출할 수 있다
+        const programData = anchor.web3.PublicKey.findProgramAddressSync(
+            [amm.programId.toBuffer()],
+            new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111"),
+        )[0];
+        let config = await amm.account.ammConfig.fetchNullable(ammConfig);
+        if (!config) {
+            await amm.methods
+                .initializeAmmConfig({
+                    feeReceiver: payer.publicKey,
+                    defaultTradeFeeBps: new BN(30),
+                    defaultProtocolFeeNumerator: new BN(0),
+                    creationFee: new BN(0),
+                })
+                .accountsPartial({ ammConfig, admin: payer.publicKey, programData })
+                .rpc();
+            config = await amm.account.ammConfig.fetch(ammConfig);
+        }
+        const page = Buffer.alloc(4);
+        page.writeUInt32LE(Math.floor(config.poolCount.toNumber() / 64));
+        return {
+            ammConfig,
+            poolRegistry: pda(amm.programId, [Buffer.from("pool_registry"), page]),
+            feeReceiver: config.feeReceiver,
+        };
+    };
+    // 풀은 mint 주소 오름차순(coin < pc)으로만 만들 수 있다
+    const sortMints = (mintA: anchor.web3.PublicKey, mintB: anchor.web3.PublicKey) =>
+        Buffer.compare(mintA.toBuffer(), mintB.toBuffer()) < 0 ? [mintA, mintB] : [mintB, mintA];
+
+    const expectError = async (promise: Promise<unknown>, code: string) => {
+        try {
+            await promise;
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal(code);
+            return;
+        }
+        expect.fail(`expected ${code}`);
+    };
+
+    const RATE = 1_000;
+    const FUND = 1_000_000;
+
+    let pool: Record<string, anchor.web3.PublicKey>;
+    let farm: anchor.web3.PublicKey;
+    let lpVault: anchor.web3.PublicKey;
+    let rewardMint: anchor.web3.PublicKey;
+    let rewardVault: anchor.web3.PublicKey;
+    let user: anchor.web3.Keypair;
+    let userLp: anchor.web3.PublicKey;
+    let userReward: anchor.web3.PublicKey;
+    let userStake: anchor.web3.PublicKey;
+
+    before(async () => {
+        // ammv2 풀을 만들고 payer가 공급해 LP를 받는다
+        const [coinMint, pcMint] = sortMints(
+            await createMint(provider.connection, payer, payer.publicKey, null, 6),
+            await createMint(provider.connection, payer, payer.publicKey, null, 6),
+        );
+        const ammPda = (seeds: Buffer[]) => pda(amm.programId, seeds);
+        const ammInfo = ammPda([Buffer.from("amm_info"), coinMint.toBuffer(), pcMint.toBuffer()]);
+        const lpMint = ammPda([Buffer.from("lp_mint"), coinMint.toBuffer(), pcMint.toBuffer()]);
+        pool = {
+            ammInfo,
+            ammAuthority: ammPda([Buffer.from("amm_authority"), coinMint.toBuffer(), pcMint.toBuffer()]),
+            coinMint,
+            pcMint,
+            coinVault: ammPda([Buffer.from("coin_vault"), ammInfo.toBuffer()]),
+            pcVault: ammPda([Buffer.from("pc_vault"), ammInfo.toBuffer()]),
+            lpMint,
+            lockedLpVault: ammPda([Buffer.from("locked_lp"), lpMint.toBuffer()]),
+        };
+        await amm.methods
+            .initializePool(new BN(0), new BN(30), { constantProduct: {} }, new BN(0))
+            .accountsPartial({ ...pool, ...(await factoryAccounts()), payer: payer.publicKey })
+            .rpc();
+
+        user = anchor.web3.Keypair.generate();
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(user.publicKey, anchor.web3.LAMPORTS_PER_SOL),
+        );
+        const userCoin = await createAccount(provider.connection, payer, coinMint, user.publicKey);
+        const userPc = await createAccount(provider.connection, payer, pcMint, user.publicKey);
+        userLp = await createAccount(provider.connection, payer, lpMint, user.publicKey);
+        await mintTo(provider.connection, payer, coinMint, userCoin, payer, 1_000_000);
+        await mintTo(provider.connection, payer, pcMint, userPc, payer, 1_000_000);
+        await amm.methods
+            .deposit(new BN(1_000_000), new BN(1_000_000), new BN(0), null)
+            .accountsPartial({
+                ...pool,
+                userCoinAccount: userCoin,
+                userPcAccount: userPc,
+                userLpAccount: userLp,
+                userAuthority: user.publicKey,
+            })
+            .signers([user])
+            .rpc();
+
+        farm = pda(program.programId, [Buffer.from("farm"), lpMint.toBuffer()]);
+        lpVault = pda(program.programId, [Buffer.from("lp_vault"), farm.toBuffer()]);
+        rewardMint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+        rewardVault = pda(program.programId, [Buffer.from("reward_vault"), farm.toBuffer(), rewardMint.toBuffer()]);
+        userReward = await createAccount(provider.connection, payer, rewardMint, user.publicKey);
+        userStake = pda(program.programId, [Buffer.from("user_stake"), farm.toBuffer(), user.publicKey.toBuffer()]);
+    });
+
+    it("only the pool admin can create the farm", async () => {
+        await expectError(
+            program.methods
+                .initializeFarm()
+                .accountsPartial({ farm, ammInfo: pool.ammInfo, lpMint: pool.lpMint, lpVault, admin: user.publicKey })
+                .signers([user])
+                .rpc(),
+            "Unauthorized",
+        );
+
+        await program.methods
+            .initializeFarm()
+            .accountsPartial({ farm, ammInfo: pool.ammInfo, lpMint: pool.lpMint, lpVault, admin: payer.publicKey })
+            .rpc();
+    });
+
+    it("adds and funds a reward", async () => {
+        await program.methods
+            .addReward(new BN(RATE))
+            .accountsPartial({ farm, rewardMint, rewardVault, admin: payer.publicKey })
+            .rpc();
+
+        const adminReward = await createAccount(provider.connection, payer, rewardMint, payer.publicKey);
+        await mintTo(provider.connection, payer, rewardMint, adminReward, payer, FUND);
+        await program.methods
+            .fund(0, new BN(FUND))
+            .accountsPartial({ farm, rewardMint, rewardVault, adminRewardAccount: adminReward, admin: payer.publicKey })
+            .rpc();
+        expect(await balance(rewardVault)).to.equal(FUND);
+    });
+
+    it("stakes, accrues and claims rewards", async () => {
+        await program.methods.createUserStake().accountsPartial({ farm, userStake, owner: user.publicKey }).signers([user]).rpc();
+
+        const staked = await balance(userLp);
+        await program.methods
+            .stake(new BN(staked))
+            .accountsPartial({ farm, userStake, lpMint: pool.lpMint, lpVault, userLpAccount: userLp, owner: user.publicKey })
+            .signers([user])
+            .rpc();
+        expect(await balance(lpVault)).to.equal(staked);
+
+        await sleep(2_000);
+        await program.methods
+            .claim(0)
+            .accountsPartial({ farm, userStake, rewardMint, rewardVault, userRewardAccount: userReward, owner: user.publicKey })
+            .signers([user])
+            .rpc();
+
+        // 유일한 스테이커: 경과 시간 × 배출량 (나머지 때문에 1 이하 차이)
+        const claimed = await balance(userReward);
+        const farmAccount = await program.account.farm.fetch(farm);
+        expect(claimed).to.be.greaterThan(0);
+        expect(claimed).to.be.at.most(FUND - farmAccount.rewards[0].remaining.toNumber());
+        expect(claimed).to.be.at.least(FUND - farmAccount.rewards[0].remaining.toNumber() - 1);
+    });
+
+    it("unstakes and keeps accrued rewards claimable", async () => {
+        const staked = (await program.account.userStake.fetch(userStake)).amount.toNumber();
+        await expectError(
+            program.methods
+                .unstake(new BN(staked + 1))
+                .accountsPartial({ farm, userStake, lpMint: pool.lpMint, lpVault, userLpAccount: userLp, owner: user.publicKey })
+                .signers([user])
+                .rpc(),
+            "InsufficientStake",
+        );
+
+        await program.methods
+            .unstake(new BN(staked))
+            .accountsPartial({ farm, userStake, lpMint: pool.lpMint, lpVault, userLpAccount: userLp, owner: user.publicKey })
+            .signers([user])
+            .rpc();
+        expect(await balance(userLp)).to.equal(staked);
+        expect((await program.account.farm.fetch(farm)).totalStaked.toNumber()).to.equal(0);
+    });
+
+    it("rejects admin calls from others", async () => {
+        await expectError(
+            program.methods.setRate(0, new BN(RATE * 2)).accountsPartial({ farm, admin: user.publicKey }).signers([user]).rpc(),
+            "Unauthorized",
+        );
+        await expectError(
+            program.methods.setRate(1, new BN(RATE)).accountsPartial({ farm, admin: payer.publicKey }).rpc(),
+            "InvalidRewardIndex",
+        );
+    });
+
+    it("credits only what reaches the vault for transfer-fee reward mints", async () => {
+        // 1% transfer fee가 걸린 Token-2022 보상 mint
+        const feeMint = anchor.web3.Keypair.generate();
+        const space = getMintLen([ExtensionType.TransferFeeConfig]);
+        await provider.sendAndConfirm(
+            new anchor.web3.Transaction().add(
+                anchor.web3.SystemProgram.createAccount({
+                    fromPubkey: payer.publicKey,
+                    newAccountPubkey: feeMint.publicKey,
+                    space,
+                    lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+                    programId: TOKEN_2022_PROGRAM_ID,
+                }),
+                createInitializeTransferFeeConfigInstruction(
+                    feeMint.publicKey,
+                    payer.publicKey,
+                    payer.publicKey,
+                    100,
+                    BigInt(1_000_000_000),
+                    TOKEN_2022_PROGRAM_ID,
+                ),
+                createInitializeMintInstruction(feeMint.publicKey, 6, payer.publicKey, null, TOKEN_2022_PROGRAM_ID),
+            ),
+            [feeMint],
+        );
+        const rewardMint = feeMint.publicKey;
+        const rewardVault = pda(program.programId, [Buffer.from("reward_vault"), farm.toBuffer(), rewardMint.toBuffer()]);
+        const tokenProgram = TOKEN_2022_PROGRAM_ID;
+
+        await program.methods
+            .addReward(new BN(RATE))
+            .accountsPartial({ farm, rewardMint, rewardVault, admin: payer.publicKey, tokenProgram })
+            .rpc();
+        const adminReward = await createAccount(
+            provider.connection, payer, rewardMint, payer.publicKey, undefined, undefined, tokenProgram,
+        );
+        await mintTo(provider.connection, payer, rewardMint, adminReward, payer, FUND, [], undefined, tokenProgram);
+        await program.methods
+            .fund(1, new BN(FUND))
+            .accountsPartial({ farm, rewardMint, rewardVault, adminRewardAccount: adminReward, admin: payer.publicKey, tokenProgram })
+            .rpc();
+
+        // 1% 수수료를 뗀 양만 도착하고, 그만큼만 보상으로 적립된다
+        const received = Number((await getAccount(provider.connection, rewardVault, undefined, tokenProgram)).amount);
+        expect(received).to.equal(FUND - FUND / 100);
+        const farmAccount = await program.account.farm.fetch(farm);
+        expect(farmAccount.rewards[1].remaining.toNumber()).to.equal(received);
+    });
+});
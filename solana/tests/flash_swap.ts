@@ -0,0 +1,218 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Ammv2 } from "../target/types/ammv2";
+import { FlashReceiver } from "../target/types/flash_receiver";
+import { createAccount, createMint, getAccount, mintTo, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("flash swap", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    const amm = anchor.workspace.Ammv2 as Program<Ammv2>;
+    const receiver = anchor.workspace.FlashReceiver as Program<FlashReceiver>;
+
+    const provider = anchor.getProvider() as anchor.AnchorProvider;
+    const payer = (provider.wallet as anchor.Wallet).payer;
+
+    const pda = (programId: anchor.web3.PublicKey, seeds: Buffer[]) =>
+        anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+    const receiverAuthority = pda(receiver.programId, [Buffer.from("receiver_authority")]);
+
+    const balance = async (account: anchor.web3.PublicKey) =>
+        Number((await getAccount(provider.connection, account)).amount);
+
+    // 전역 설정은 프로그램에 하나 — 없으면 만들고, initialize_pool에 넘길 팩토리 계정을 돌려준다
+    const factoryAccounts = async () => {
+        const ammConfig = pda(amm.programId, [Buffer.from("amm_config")]);
+        // initialize_amm_config는 업그레이드 권한자(로컬 배포자 = payer)만 호출할 수 있다
+        const programData = anchor.web3.PublicKey.findProgramAddressSync(
+            [amm.programId.toBuffer()],
+            new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111"),
+        )[0];
+        let config = await amm.account.ammConfig.fetchNullable(ammConfig);
+        if (!config) {
+            await amm.methods
+                .initializeAmmConfig({
+                    feeReceiver: payer.publicKey,
+                    defaultTradeFeeBps: new BN(30),
+                    defaultProtocolFeeNumerator: new BN(0),
+                    creationFee: new BN(0),
+                })
+                .accountsPartial({ ammConfig, admin: payer.publicKey, programData })
+                .rpc();
+            config = await amm.account.ammConfig.fetch(ammConfig);
+        }
+        const page = Buffer.alloc(4);
+        page.writeUInt32LE(Math.floor(config.poolCount.toNumber() / 64));
+        return {
+            ammConfig,
+            poolRegistry: pda(amm.programId, [Buffer.from("pool_registry"), page]),
+            feeReceiver: config.feeReceiver,
+        };
+    };
+    // 풀은 mint 주소 오름차순(coin < pc)으로만 만들 수 있다
+    const sortMints = (mintA: anchor.web3.PublicKey, mintB: anchor.web3.PublicKey) =>
+        Buffer.compare(mintA.toBuffer(), mintB.toBuffer()) < 0 ? [mintA, mintB] : [mintB, mintA];
+
+    const LIQUIDITY = 10_000_000;
+    let pool: Record<string, anchor.web3.PublicKey>;
+    let receiverCoin: anchor.web3.PublicKey;
+    let receiverPc: anchor.web3.PublicKey;
+
+    before(async () => {
+        const [coinMint, pcMint] = sortMints(
+            await createMint(provider.connection, payer, payer.publicKey, null, 6),
+            await createMint(provider.connection, payer, payer.publicKey, null, 6),
+        );
+        const ammInfo = pda(amm.programId, [Buffer.from("amm_info"), coinMint.toBuffer(), pcMint.toBuffer()]);
+        const lpMint = pda(amm.programId, [Buffer.from("lp_mint"), coinMint.toBuffer(), pcMint.toBuffer()]);
+        pool = {
+            ammInfo,
+            ammAuthority: pda(amm.programId, [Buffer.from("amm_authority"), coinMint.toBuffer(), pcMint.toBuffer()]),
+            coinMint,
+            pcMint,
+            coinVault: pda(amm.programId, [Buffer.from("coin_vault"), ammInfo.toBuffer()]),
+            pcVault: pda(amm.programId, [Buffer.from("pc_vault"), ammInfo.toBuffer()]),
+            lpMint,
+            lockedLpVault: pda(amm.programId, [Buffer.from("locked_lp"), lpMint.toBuffer()]),
+            tokenProgram: TOKEN_PROGRAM_ID,
+        };
+        await amm.methods
+            .initializePool(new BN(0), new BN(30), { constantProduct: {} }, new BN(0))
+            .accountsPartial({ ...pool, ...(await factoryAccounts()), payer: payer.publicKey })
+            .rpc();
+
+        const coin = await createAccount(provider.connection, payer, coinMint, payer.publicKey);
+        const pc = await createAccount(provider.connection, payer, pcMint, payer.publicKey);
+        const lp = await createAccount(provider.connection, payer, lpMint, payer.publicKey);
+        await mintTo(provider.connection, payer, coinMint, coin, payer, LIQUIDITY);
+        await mintTo(provider.connection, payer, pcMint, pc, payer, LIQUIDITY);
+        await amm.methods
+            .deposit(new BN(LIQUIDITY), new BN(LIQUIDITY), new BN(0), null)
+            .accountsPartial({
+                ...pool,
+                userCoinAccount: coin,
+                userPcAccount: pc,
+                userLpAccount: lp,
+                userAuthority: payer.publicKey,
+            })
+            .rpc();
+        await amm.methods
+            .setPoolStatus({ trading: {} })
+            .accountsPartial({ ammInfo: pool.ammInfo, admin: payer.publicKey })
+            .rpc();
+
+        // receiver 프로그램 PDA 소유 계정 — 수수료를 낼 만큼 미리 충전
+        receiverCoin = await createAccount(
+            provider.connection, payer, coinMint, receiverAuthority, anchor.web3.Keypair.generate(),
+        );
+        receiverPc = await createAccount(
+            provider.connection, payer, pcMint, receiverAuthority, anchor.web3.Keypair.generate(),
+        );
+        await mintTo(provider.connection, payer, coinMint, receiverCoin, payer, 10_000);
+        await mintTo(provider.connection, payer, pcMint, receiverPc, payer, 10_000);
+    });
+
+    // flash_receiver의 data[0] 모드
+    const NO_REPAY = 0;
+    const REPAY = 1;
+    const DRAIN = 2;
+
+    const flashSwap = (coinAmount: number, pcAmount: number, mode: number, tokenProgram = pool.tokenProgram) =>
+        amm.methods
+            .flashSwap(new BN(coinAmount), new BN(pcAmount), Buffer.from([mode]))
+            .accountsPartial({
+                ...pool,
+                receiverCoinAccount: receiverCoin,
+                receiverPcAccount: receiverPc,
+                callbackProgram: receiver.programId,
+                initiator: payer.publicKey,
+            })
+            // 콜백 계정 순서: amm_authority(자동) 다음 FlashSwapCallback의 나머지 계정,
+            // 토큰 프로그램은 풀이 쓰는 것을 그대로 넘긴다
+            .remainingAccounts(
+                [
+                    { pubkey: receiverAuthority, isWritable: false },
+                    { pubkey: pool.coinMint, isWritable: false },
+                    { pubkey: pool.pcMint, isWritable: false },
+                    { pubkey: pool.coinVault, isWritable: true },
+                    { pubkey: pool.pcVault, isWritable: true },
+                    { pubkey: receiverCoin, isWritable: true },
+                    { pubkey: receiverPc, isWritable: true },
+                    { pubkey: tokenProgram, isWritable: false },
+                ].map((account) => ({ ...account, isSigner: false })),
+            )
+            .rpc();
+
+    it("lends coin and accepts repayment with the fee", async () => {
+        const vaultBefore = await balance(pool.coinVault);
+        const receiverBefore = await balance(receiverCoin);
+
+        await flashSwap(1_000_000, 0, REPAY);
+
+        // fee = ceil(1_000_000 * 30 / 9_970) = 3_010
+        expect(await balance(pool.coinVault)).to.equal(vaultBefore + 3_010);
+        expect(await balance(receiverCoin)).to.equal(receiverBefore - 3_010);
+    });
+
+    it("lends both sides at once", async () => {
+        const coinBefore = await balance(pool.coinVault);
+        const pcBefore = await balance(pool.pcVault);
+
+        await flashSwap(100_000, 200_000, REPAY);
+
+        expect(await balance(pool.coinVault)).to.be.greaterThan(coinBefore);
+        expect(await balance(pool.pcVault)).to.be.greaterThan(pcBefore);
+    });
+
+    it("reverts when the callback does not repay", async () => {
+        const vaultBefore = await balance(pool.coinVault);
+        try {
+            await flashSwap(1_000_000, 0, NO_REPAY);
+            expect.fail("flash swap should have failed");
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal("FlashSwapNotRepaid");
+        }
+        expect(await balance(pool.coinVault)).to.equal(vaultBefore);
+    });
+
+    it("does not let the callback move vault funds with the pool authority", async () => {
+        const vaultBefore = await balance(pool.coinVault);
+        try {
+            await flashSwap(1_000, 0, DRAIN);
+            expect.fail("flash swap should have failed");
+        } catch (err) {
+            // amm_authority는 서명 없이 전달되므로 런타임이 권한 상승으로 거부한다
+            expect(String(err)).to.match(/unauthorized signer/);
+        }
+        expect(await balance(pool.coinVault)).to.equal(vaultBefore);
+    });
+
+    it("rejects a callback token program that does not match the pool", async () => {
+        const vaultBefore = await balance(pool.coinVault);
+        try {
+            await flashSwap(1_000, 0, REPAY, TOKEN_2022_PROGRAM_ID);
+            expect.fail("flash swap should have failed");
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal("ConstraintMintTokenProgram");
+        }
+        expect(await balance(pool.coinVault)).to.equal(vaultBefore);
+    });
+
+    it("rejects the amm program itself as the callback", async () => {
+        try {
+            await amm.methods
+                .flashSwap(new BN(1_000), new BN(0), Buffer.from([]))
+                .accountsPartial({
+                    ...pool,
+                    receiverCoinAccount: receiverCoin,
+                    receiverPcAccount: receiverPc,
+                    callbackProgram: amm.programId,
+                    initiator: payer.publicKey,
+                })
+                .rpc();
+            expect.fail("flash swap should have failed");
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal("InvalidCallbackProgram");
+        }
+    });
+});
@@ -0,0 +1,240 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Ammv2 } from "../target/types/ammv2";
+import { Router } from "../target/types/router";
+import { createAccount, createMint, getAccount, mintTo, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { expect } from "chai";
+
+describe("router", () => {
+    anchor.setProvider(anchor.AnchorProvider.env());
+    const amm = anchor.workspace.Ammv2 as Program<Ammv2>;
+    const program = anchor.workspace.Router as Program<Router>;
+
+    const provider = anchor.getProvider() as anchor.AnchorProvider;
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const user = anchor.web3.Keypair.generate();
+
+    const pda = (programId: anchor.web3.PublicKey, seeds: Buffer[]) =>
+        anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+    const routerAuthority = pda(program.programId, [Buffer.from("router_authority")]);
+    const hopAccount = (mint: anchor.web3.PublicKey) =>
+        pda(program.programId, [Buffer.from("hop_account"), mint.toBuffer()]);
+
+    const balance = async (account: anchor.web3.PublicKey) =>
+        Number((await getAccount(provider.connection, account)).amount);
+
+    // 전역 설정은 프로그램에 하나 — 없으면 만들고, initialize_pool에 넘길 팩토리 계정을 돌려준다
+    const factoryAccounts = async () => {
+        const ammConfig = pda(amm.programId, [Buffer.from("amm_config")]);
+        // initialize_amm_config는 업그레이드 권한자(로컬 배포자 = payer)만 호출할 수 있다
+        const programData = anchor.web3.PublicKey.findProgramAddressSync(
+            [amm.programId.toBuffer()],
+            new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111"),
+        )[0];
+        let config = await amm.account.ammConfig.fetchNullable(ammConfig);
+        if (!config) {
+            await amm.methods
+                .initializeAmmConfig({
+                    feeReceiver: payer.publicKey,
+                    defaultTradeFeeBps: new BN(30),
+                    defaultProtocolFeeNumerator: new BN(0),
+                    creationFee: new BN(0),
+                })
+                .accountsPartial({ ammConfig, admin: payer.publicKey, programData })
+                .rpc();
+            config = await amm.account.ammConfig.fetch(ammConfig);
+        }
+        const page = Buffer.alloc(4);
+        page.writeUInt32LE(Math.floor(config.poolCount.toNumber() / 64));
+        return {
+            ammConfig,
+            poolRegistry: pda(amm.programId, [Buffer.from("pool_registry"), page]),
+            feeReceiver: config.feeReceiver,
+        };
+    };
+    // 풀은 mint 주소 오름차순(coin < pc)으로만 만들 수 있다
+    const sortMints = (mintA: anchor.web3.PublicKey, mintB: anchor.web3.PublicKey) =>
+        Buffer.compare(mintA.toBuffer(), mintB.toBuffer()) < 0 ? [mintA, mintB] : [mintB, mintA];
+
+    // coin/pc 풀 생성 + 유동성 공급 + 거래 오픈
+    const createPool = async (mintA: anchor.web3.PublicKey, mintB: anchor.web3.PublicKey, liquidity: number) => {
+        const [coinMint, pcMint] = sortMints(mintA, mintB);
+        const ammInfo = pda(amm.programId, [Buffer.from("amm_info"), coinMint.toBuffer(), pcMint.toBuffer()]);
+        const lpMint = pda(amm.programId, [Buffer.from("lp_mint"), coinMint.toBuffer(), pcMint.toBuffer()]);
+        const pool = {
+            ammInfo,
+            ammAuthority: pda(amm.programId, [Buffer.from("amm_authority"), coinMint.toBuffer(), pcMint.toBuffer()]),
+            coinMint,
+            pcMint,
+            coinVault: pda(amm.programId, [Buffer.from("coin_vault"), ammInfo.toBuffer()]),
+            pcVault: pda(amm.programId, [Buffer.from("pc_vault"), ammInfo.toBuffer()]),
+            lpMint,
+            lockedLpVault: pda(amm.programId, [Buffer.from("locked_lp"), lpMint.toBuffer()]),
+            tokenProgram: TOKEN_PROGRAM_ID,
+        };
+        await amm.methods
+            .initializePool(new BN(0), new BN(30), { constantProduct: {} }, new BN(0))
+            .accountsPartial({ ...pool, ...(await factoryAccounts()), payer: payer.publicKey })
+            .rpc();
+
+        const coin = await createAccount(provider.connection, payer, coinMint, payer.publicKey, anchor.web3.Keypair.generate());
+        const pc = await createAccount(provider.connection, payer, pcMint, payer.publicKey, anchor.web3.Keypair.generate());
+        const lp = await createAccount(provider.connection, payer, lpMint, payer.publicKey);
+        await mintTo(provider.connection, payer, coinMint, coin, payer, liquidity);
+        await mintTo(provider.connection, payer, pcMint, pc, payer, liquidity);
+        await amm.methods
+            .deposit(new BN(liquidity), new BN(liquidity), new BN(0), null)
+            .accountsPartial({
+                ...pool,
+                userCoinAccount: coin,
+                userPcAccount: pc,
+                userLpAccount: lp,
+                userAuthority: payer.publicKey,
+            })
+            .rpc();
+        await amm.methods
+            .setPoolStatus({ trading: {} })
+            .accountsPartial({ ammInfo: pool.ammInfo, admin: payer.publicKey })
+            .rpc();
+        return pool;
+    };
+    type Pool = Awaited<ReturnType<typeof createPool>>;
+
+    // hop 하나의 remaining accounts (순서 고정): 풀 계정, 토큰 프로그램, lot 나머지를 돌려받을 사용자 계정
+    const hopAccounts = (pool: Pool, refund: anchor.web3.PublicKey) => [
+        ...[
+            pool.ammInfo,
+            pool.ammAuthority,
+            pool.coinMint,
+            pool.pcMint,
+            pool.coinVault,
+            pool.pcVault,
+            hopAccount(pool.coinMint),
+            hopAccount(pool.pcMint),
+        ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })),
+        { pubkey: pool.tokenProgram, isSigner: false, isWritable: false },
+        { pubkey: refund, isSigner: false, isWritable: true },
+    ];
+
+    let mintA: anchor.web3.PublicKey;
+    let usdc: anchor.web3.PublicKey;
+    let mintB: anchor.web3.PublicKey;
+    let poolA: Pool;
+    let poolB: Pool;
+    let userA: anchor.web3.PublicKey;
+    let userUsdc: anchor.web3.PublicKey;
+    let userB: anchor.web3.PublicKey;
+
+    before(async () => {
+        await provider.connection.confirmTransaction(
+            await provider.connection.requestAirdrop(user.publicKey, anchor.web3.LAMPORTS_PER_SOL),
+        );
+        mintA = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+        usdc = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+        mintB = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+
+        // A/USDC, USDC/B — 각 풀에서 coin/pc 역할은 mint 주소 순서로 정해진다
+        poolA = await createPool(mintA, usdc, 10_000_000);
+        poolB = await createPool(usdc, mintB, 10_000_000);
+
+        for (const mint of [mintA, usdc, mintB]) {
+            await program.methods
+                .initializeHopAccount()
+                .accountsPartial({
+                    routerAuthority,
+                    mint,
+                    hopAccount: hopAccount(mint),
+                    payer: payer.publicKey,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+        }
+
+        userA = await createAccount(provider.connection, payer, mintA, user.publicKey);
+        userUsdc = await createAccount(provider.connection, payer, usdc, user.publicKey);
+        userB = await createAccount(provider.connection, payer, mintB, user.publicKey);
+        await mintTo(provider.connection, payer, mintA, userA, payer, 1_000_000);
+    });
+
+    const routeSwap = (amountIn: number, minimumAmountOut: number, pools: Pool[]) =>
+        program.methods
+            .routeSwap(new BN(amountIn), new BN(minimumAmountOut), null)
+            .accountsPartial({
+                routerAuthority,
+                userSourceAccount: userA,
+                userDestinationAccount: userB,
+                userAuthority: user.publicKey,
+                ammv2Program: amm.programId,
+            })
+            // 각 hop의 입력 mint: A, USDC
+            .remainingAccounts(pools.flatMap((pool, index) => hopAccounts(pool, [userA, userUsdc][index])))
+            .signers([user])
+            .rpc();
+
+    it("routes A -> USDC -> B without touching the user's USDC", async () => {
+        await routeSwap(100_000, 90_000, [poolA, poolB]);
+
+        expect(await balance(userA)).to.equal(900_000);
+        expect(await balance(userUsdc)).to.equal(0);
+        expect(await balance(userB)).to.be.greaterThan(90_000);
+
+        // 중간 hop 계정에 남는 잔고 없음
+        for (const mint of [mintA, usdc, mintB]) {
+            expect(await balance(hopAccount(mint))).to.equal(0);
+        }
+    });
+
+    it("enforces minimum_amount_out on the final leg", async () => {
+        try {
+            await routeSwap(100_000, 100_000, [poolA, poolB]);
+            expect.fail("route should have failed");
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal("SlippageExceeded");
+        }
+    });
+
+    it("rejects a path that does not connect", async () => {
+        try {
+            await routeSwap(100_000, 0, [poolB, poolA]);
+            expect.fail("route should have failed");
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal("InvalidPath");
+        }
+    });
+
+    it("rejects a token program that does not match the pool", async () => {
+        try {
+            await routeSwap(100_000, 0, [poolA, { ...poolB, tokenProgram: TOKEN_2022_PROGRAM_ID }]);
+            expect.fail("route should have failed");
+        } catch (err) {
+            expect(err.error.errorCode.code).to.equal("InvalidTokenProgram");
+        }
+    });
+
+    it("rounds each hop to the pool's lot size and refunds the remainder", async () => {
+        // USDC → B 풀의 USDC 입력 lot을 1_000으로
+        const usdcIsCoin = poolB.coinMint.equals(usdc);
+        await amm.methods
+            .updatePoolParams({
+                coinLotSize: new BN(usdcIsCoin ? 1_000 : 1),
+                pcLotSize: new BN(usdcIsCoin ? 1 : 1_000),
+                minSize: new BN(1),
+                minPriceMultiplier: new BN(0),
+                maxPriceMultiplier: new BN(0),
+                volMaxCutRatio: new BN(0),
+            })
+            .accountsPartial({ ammInfo: poolB.ammInfo, admin: payer.publicKey })
+            .rpc();
+
+        const usdcBefore = await balance(userUsdc);
+        const bBefore = await balance(userB);
+        await routeSwap(100_000, 1, [poolA, poolB]);
+
+        // 첫 hop 출력 중 lot에 못 미치는 나머지만 사용자 USDC 계정으로 돌아오고 hop 계정에는 남지 않는다
+        expect((await balance(userUsdc)) - usdcBefore).to.be.lessThan(1_000);
+        expect(await balance(userB)).to.be.greaterThan(bBefore);
+        for (const mint of [mintA, usdc, mintB]) {
+            expect(await balance(hopAccount(mint))).to.equal(0);
+        }
+    });
+});